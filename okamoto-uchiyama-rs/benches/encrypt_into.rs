@@ -0,0 +1,78 @@
+//! Reused output buffer benchmark.
+//!
+//! Run with `cargo bench --bench encrypt_into`.
+//!
+//! Encrypts the same plaintexts with the allocating `encrypt_with_rng`, with `encrypt_into`
+//! into a reused `BigUint` and with `encrypt_bytes_into` into a reused byte buffer, and
+//! reports the mean time of an encryption for each. The exponentiations dominate the cost,
+//! so only a small difference is expected.
+
+use num_bigint_dig::{BigUint, RandBigInt};
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::OkamotoUchiyama;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Number of timed encryptions per variant
+const SAMPLES: usize = 200;
+
+fn main() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = &private_key.public_key;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let messages: Vec<BigUint> = (0..SAMPLES)
+        .map(|_| rng.gen_biguint_below(&private_key.p))
+        .collect();
+
+    // Warm up the caches
+    for message in &messages[..SAMPLES / 10] {
+        black_box(OkamotoUchiyama::encrypt_with_rng(
+            message, public_key, &mut rng,
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let start = Instant::now();
+    for message in &messages {
+        black_box(OkamotoUchiyama::encrypt_with_rng(
+            black_box(message),
+            public_key,
+            &mut rng,
+        ));
+    }
+    let allocating = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+    println!("{:<20} encrypt {:>10.0} ns", "encrypt", allocating);
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut out = BigUint::default();
+    let start = Instant::now();
+    for message in &messages {
+        public_key.encrypt_into(black_box(message), &mut out, &mut rng);
+        black_box(&out);
+    }
+    let into = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+    println!(
+        "{:<20} encrypt {:>10.0} ns  speedup {:.2}",
+        "encrypt_into",
+        into,
+        allocating / into
+    );
+
+    let mut out = Vec::new();
+    let start = Instant::now();
+    for message in &messages {
+        public_key.encrypt_bytes_into(black_box(message), &mut out);
+        black_box(&out);
+    }
+    let bytes_into = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+    println!(
+        "{:<20} encrypt {:>10.0} ns  speedup {:.2}",
+        "encrypt_bytes_into",
+        bytes_into,
+        allocating / bytes_into
+    );
+}
@@ -0,0 +1,75 @@
+//! Encryption tables benchmark.
+//!
+//! Run with `cargo bench --bench encryption_tables`.
+//!
+//! Encrypts the same plaintexts with the plain exponentiation and with precomputed
+//! encryption tables of 2, 4 and 6-bit windows, and reports for each the time taken to
+//! build the tables and the mean time of an encryption. The randomness of every encryption
+//! is drawn from the same seed, so all the variants compute the same ciphertexts.
+//!
+//! Small windows need more multiplications per exponentiation and are expected to be slower
+//! than the plain exponentiation; larger windows trade memory and setup time for speed.
+
+use num_bigint_dig::{BigUint, RandBigInt};
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::{OkamotoUchiyama, PublicKey};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Number of timed encryptions per variant
+const SAMPLES: usize = 200;
+
+// Window sizes compared with the plain exponentiation
+const WINDOW_BITS: [u32; 3] = [2, 4, 6];
+
+// Times SAMPLES encryptions and returns the mean time of one, in nanoseconds
+fn time_encryptions(messages: &[BigUint], public_key: &PublicKey) -> f64 {
+    let mut rng = StdRng::seed_from_u64(1);
+    let start = Instant::now();
+    for message in messages {
+        black_box(OkamotoUchiyama::encrypt_with_rng(
+            black_box(message),
+            public_key,
+            &mut rng,
+        ));
+    }
+    start.elapsed().as_nanos() as f64 / messages.len() as f64
+}
+
+fn main() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = &private_key.public_key;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let messages: Vec<BigUint> = (0..SAMPLES)
+        .map(|_| rng.gen_biguint_below(&private_key.p))
+        .collect();
+
+    // Warm up the caches
+    time_encryptions(&messages[..SAMPLES / 10], public_key);
+
+    let plain = time_encryptions(&messages, public_key);
+    println!(
+        "{:<8} setup {:>10} ms  encrypt {:>10.0} ns",
+        "plain", "-", plain
+    );
+
+    for window_bits in WINDOW_BITS {
+        let mut tabled_key = public_key.clone();
+        let start = Instant::now();
+        tabled_key.precompute_encryption_tables(window_bits);
+        let setup = start.elapsed().as_millis();
+
+        let tabled = time_encryptions(&messages, &tabled_key);
+        println!(
+            "{:<8} setup {:>10} ms  encrypt {:>10.0} ns  speedup {:.2}",
+            format!("w = {}", window_bits),
+            setup,
+            tabled,
+            plain / tabled
+        );
+    }
+}
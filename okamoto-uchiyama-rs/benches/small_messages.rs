@@ -0,0 +1,75 @@
+//! Small message exponentiation benchmark.
+//!
+//! Run with `cargo bench --bench small_messages`.
+//!
+//! Computes g^m mod n for messages of 4 to 32 bits with the general `modpow` and with the
+//! plain square-and-multiply used by the encryption for small messages, and reports the mean
+//! time of an exponentiation for both. This is what the small message threshold of the
+//! encryption is chosen from.
+
+use num::One;
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::OkamotoUchiyama;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::Instant;
+
+// Number of timed exponentiations per variant
+const SAMPLES: usize = 2000;
+
+// Message sizes compared, in bits
+const MESSAGE_BITS: [u32; 5] = [4, 8, 16, 24, 32];
+
+// Computes base^exponent mod modulus with a plain square-and-multiply
+fn square_and_multiply(base: &BigUint, exponent: u32, modulus: &BigUint) -> BigUint {
+    let mut result = BigUint::one() % modulus;
+    for i in (0..u32::BITS - exponent.leading_zeros()).rev() {
+        result = (&result * &result) % modulus;
+        if (exponent >> i) & 1 == 1 {
+            result = (&result * base) % modulus;
+        }
+    }
+    result
+}
+
+fn main() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = &private_key.public_key;
+    let (g, n) = (&public_key.g, &public_key.n);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for bits in MESSAGE_BITS {
+        let messages: Vec<u32> = (0..SAMPLES)
+            .map(|_| rng.gen_range(0..=u32::MAX >> (u32::BITS - bits)))
+            .collect();
+        let exponents: Vec<BigUint> = messages.iter().map(|&m| BigUint::from(m)).collect();
+
+        // Warm up the caches
+        for exponent in &exponents[..SAMPLES / 10] {
+            black_box(g.modpow(exponent, n));
+        }
+
+        let start = Instant::now();
+        for exponent in &exponents {
+            black_box(g.modpow(black_box(exponent), n));
+        }
+        let modpow = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+
+        let start = Instant::now();
+        for &message in &messages {
+            black_box(square_and_multiply(g, black_box(message), n));
+        }
+        let plain = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+
+        println!(
+            "{:<9} modpow {:>10.0} ns  square-and-multiply {:>10.0} ns  speedup {:.2}",
+            format!("{} bits", bits),
+            modpow,
+            plain,
+            modpow / plain
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Subtraction context benchmark.
+//!
+//! Run with `cargo bench --bench subtraction_context`.
+//!
+//! Subtracts the same ciphertext (e.g. an encrypted mean) from a batch of ciphertexts, once
+//! with `SignedCiphertext::homomorphic_subtract`, which inverts the subtrahend on every call,
+//! and once with a `SubtractionContext`, which inverts it once up front. Reports the mean time
+//! of a subtraction for both, and the time taken to build the context.
+
+use num_bigint_dig::{BigUint, RandBigInt};
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::signed_ciphertext::SignedCiphertext;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::OkamotoUchiyama;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Number of timed subtractions per variant
+const SAMPLES: usize = 1000;
+
+fn main() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = &private_key.public_key;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let ciphertexts: Vec<Ciphertext> = (0..SAMPLES)
+        .map(|_| {
+            let message: BigUint = rng.gen_biguint_below(&private_key.p);
+            OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut rng)
+        })
+        .collect();
+    let mean = OkamotoUchiyama::encrypt_with_rng(&BigUint::from(1000u32), public_key, &mut rng);
+
+    let signed: Vec<SignedCiphertext> = ciphertexts
+        .iter()
+        .cloned()
+        .map(SignedCiphertext::from)
+        .collect();
+    let signed_mean = SignedCiphertext::from(mean.clone());
+
+    // Warm up the caches
+    for ciphertext in &signed[..SAMPLES / 10] {
+        black_box(
+            ciphertext
+                .homomorphic_subtract(&signed_mean, public_key)
+                .unwrap(),
+        );
+    }
+
+    let start = Instant::now();
+    for ciphertext in &signed {
+        black_box(
+            black_box(ciphertext)
+                .homomorphic_subtract(&signed_mean, public_key)
+                .unwrap(),
+        );
+    }
+    let repeated = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+    println!(
+        "{:<10} setup {:>10} us  subtract {:>10.0} ns",
+        "repeated", "-", repeated
+    );
+
+    let start = Instant::now();
+    let context = public_key.subtraction_context(&mean).unwrap();
+    let setup = start.elapsed().as_micros();
+
+    let start = Instant::now();
+    for ciphertext in &ciphertexts {
+        black_box(
+            context
+                .homomorphic_subtract_fast(black_box(ciphertext))
+                .unwrap(),
+        );
+    }
+    let fast = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+    println!(
+        "{:<10} setup {:>10} us  subtract {:>10.0} ns  speedup {:.2}",
+        "context",
+        setup,
+        fast,
+        repeated / fast
+    );
+}
@@ -0,0 +1,133 @@
+//! Decryption timing benchmark.
+//!
+//! Run with `cargo bench --bench timing`.
+//!
+//! Decrypts one fixed ciphertext and a set of ciphertexts of varied plaintexts the same
+//! number of times, interleaving both, and reports for each set the mean, the standard
+//! deviation and the coefficient of variation (standard deviation / mean) of a decryption,
+//! after dropping the slowest 5% of the samples, which are mostly scheduler noise.
+//!
+//! The figure to look at is the relative difference between the two means: decryption time
+//! should not depend on the ciphertext, so it is expected to stay within the noise of the
+//! machine. If it exceeds `MAX_MEAN_DIFFERENCE`, the run is flagged and exits with status 1.
+//! A high coefficient of variation in both sets points at a noisy machine rather than at the
+//! decryption: rerun on an idle machine before drawing conclusions.
+//!
+//! This is statistical evidence, not a proof: the underlying BigUint arithmetic is not
+//! guaranteed to be constant-time.
+
+use num_bigint_dig::{BigUint, RandBigInt};
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::process::ExitCode;
+use std::time::Instant;
+
+// Number of timed decryptions per set
+const SAMPLES: usize = 2000;
+
+// Number of distinct ciphertexts in the varied set
+const VARIED_CIPHERTEXTS: usize = 100;
+
+// Share of the slowest samples dropped from each set
+const OUTLIER_SHARE: f64 = 0.05;
+
+// Maximum relative difference between the mean decryption times of both sets
+const MAX_MEAN_DIFFERENCE: f64 = 0.05;
+
+// Summary of the timings of a set of decryptions, in nanoseconds
+struct Stats {
+    mean: f64,
+    std_dev: f64,
+}
+
+impl Stats {
+    // Computes the statistics of the samples, without their slowest share
+    fn new(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(f64::total_cmp);
+        samples.truncate(samples.len() - (samples.len() as f64 * OUTLIER_SHARE) as usize);
+
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+        Stats {
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+
+    fn report(&self, name: &str) {
+        println!(
+            "{:<8} mean {:>10.0} ns  std dev {:>8.0} ns  cv {:.4}",
+            name,
+            self.mean,
+            self.std_dev,
+            self.std_dev / self.mean
+        );
+    }
+}
+
+// Times a single decryption
+fn time_decryption(ciphertext: &Ciphertext, private_key: &PrivateKey) -> f64 {
+    let start = Instant::now();
+    black_box(OkamotoUchiyama::decrypt(black_box(ciphertext), private_key));
+    start.elapsed().as_nanos() as f64
+}
+
+fn main() -> ExitCode {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = &private_key.public_key;
+
+    // Plaintexts range from zero to p - 1
+    let mut rng = StdRng::seed_from_u64(0);
+    let fixed = OkamotoUchiyama::encrypt(&BigUint::from(42u32), public_key);
+    let mut varied: Vec<Ciphertext> = (0..VARIED_CIPHERTEXTS - 2)
+        .map(|_| {
+            let message = rng.gen_biguint_below(&private_key.p);
+            OkamotoUchiyama::encrypt(&message, public_key)
+        })
+        .collect();
+    varied.push(OkamotoUchiyama::encrypt(&BigUint::from(0u32), public_key));
+    varied.push(OkamotoUchiyama::encrypt(
+        &(&private_key.p - 1u32),
+        public_key,
+    ));
+
+    // Warm up the caches
+    for ciphertext in &varied {
+        time_decryption(ciphertext, &private_key);
+    }
+
+    // Interleave both sets so drifts of the machine affect them alike
+    let mut fixed_samples = Vec::with_capacity(SAMPLES);
+    let mut varied_samples = Vec::with_capacity(SAMPLES);
+    for i in 0..SAMPLES {
+        fixed_samples.push(time_decryption(&fixed, &private_key));
+        varied_samples.push(time_decryption(
+            &varied[i % VARIED_CIPHERTEXTS],
+            &private_key,
+        ));
+    }
+
+    let fixed_stats = Stats::new(fixed_samples);
+    let varied_stats = Stats::new(varied_samples);
+    fixed_stats.report("fixed");
+    varied_stats.report("varied");
+
+    let difference = (varied_stats.mean - fixed_stats.mean).abs() / fixed_stats.mean;
+    println!(
+        "relative difference of means {:.4} (threshold {:.4})",
+        difference, MAX_MEAN_DIFFERENCE
+    );
+
+    if difference > MAX_MEAN_DIFFERENCE {
+        println!("FLAGGED: decryption time depends on the ciphertext");
+        return ExitCode::FAILURE;
+    }
+    println!("ok");
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,52 @@
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::accumulator::HomomorphicAccumulator;
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::pem::PemEncodable;
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey, PublicKey};
+
+// A client encrypts its private value under the shared public key
+// and sends the PEM-encoded ciphertext to the server
+fn client(value: u64, public_key: &PublicKey) -> String {
+    OkamotoUchiyama::encrypt(&BigUint::from(value), public_key).to_pem()
+}
+
+// The server sums the ciphertexts it receives without the private key,
+// and only learns the encrypted total
+fn server(submissions: &[String], public_key: &PublicKey) -> String {
+    let mut accumulator = HomomorphicAccumulator::new(public_key);
+    for submission in submissions {
+        let ciphertext = Ciphertext::from_pem(submission).unwrap();
+        accumulator.add(&ciphertext).unwrap();
+    }
+    accumulator.total().to_pem()
+}
+
+// The decryptor holds the private key and recovers the total
+fn decryptor(total: &str, private_key: &PrivateKey) -> BigUint {
+    let ciphertext = Ciphertext::from_pem(total).unwrap();
+    OkamotoUchiyama::decrypt(&ciphertext, private_key)
+}
+
+fn main() {
+    // The decryptor generates the key pair and publishes the public key
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits1024).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let public_key = PublicKey::from_pem(&private_key.public_key.to_pem()).unwrap();
+
+    // Each client encrypts its value
+    let values = [42u64, 1337, 7, 2024, 99];
+    let submissions: Vec<String> = values
+        .iter()
+        .map(|&value| client(value, &public_key))
+        .collect();
+
+    // The server aggregates the submissions
+    let total = server(&submissions, &public_key);
+
+    // The decryptor recovers the sum of the values
+    let sum = decryptor(&total, &private_key);
+    assert_eq!(sum, BigUint::from(values.iter().sum::<u64>()));
+
+    println!("Sum of {} client values: {}", values.len(), sum);
+}
@@ -0,0 +1,133 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+use crate::pem::to_asn1_bytes;
+
+use asn1::BigUint as Asn1BigUint;
+use num::{One, Zero};
+use num_bigint_dig::BigUint;
+
+/// Accumulates ciphertexts into an encrypted running total.
+/// Since Okamoto-Uchiyama is additively homomorphic, the accumulated product
+/// decrypts to the sum of the accumulated plaintexts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HomomorphicAccumulator {
+    // The public key the accumulated ciphertexts are encrypted with
+    public_key: PublicKey,
+    // Product of the accumulated ciphertexts mod n
+    product: BigUint,
+    // Number of accumulated ciphertexts
+    count: u64,
+    // Number of additions between two rerandomizations of the total, 0 to never rerandomize
+    rerandomize_every: usize,
+}
+
+impl HomomorphicAccumulator {
+    /// Creates an empty accumulator for the given public key
+    pub fn new(public_key: &PublicKey) -> Self {
+        HomomorphicAccumulator {
+            public_key: public_key.clone(),
+            product: BigUint::one(),
+            count: 0,
+            rerandomize_every: 0,
+        }
+    }
+
+    /// Rerandomizes the running total after every `every` additions, by multiplying it
+    /// with h^r mod n for a fresh random r, i.e. adding an encryption of zero. An observer
+    /// of successive totals then cannot link them to the increments, while the decrypted
+    /// total is unchanged. Each rerandomization costs a full encryption, i.e. two modular
+    /// exponentiations, against one modular multiplication per addition, so a small
+    /// period multiplies the cost of accumulating. 0 disables it, which is the default.
+    /// The period is not part of the checkpoint of `to_bytes`.
+    pub fn with_rerandomize_every(mut self, every: usize) -> Self {
+        self.rerandomize_every = every;
+        self
+    }
+
+    /// Adds a ciphertext to the running total
+    pub fn add(&mut self, c: &Ciphertext) -> Result<(), OkamotoUchiyamaError> {
+        self.public_key.check_ciphertext(c)?;
+
+        // Multiply the ciphertext into the running product and reduce mod n
+        self.product = (&self.product * c.value()) % &self.public_key.n;
+        self.count += 1;
+
+        if self.rerandomize_every > 0 && self.count.is_multiple_of(self.rerandomize_every as u64) {
+            let zero = OkamotoUchiyama::encrypt(&BigUint::zero(), &self.public_key);
+            self.product = (&self.product * zero.value()) % &self.public_key.n;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of accumulated ciphertexts
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the encrypted running total
+    pub fn total(&self) -> Ciphertext {
+        Ciphertext::new(self.product.clone())
+    }
+
+    /// Serializes the accumulator state into a DER-encoded checkpoint.
+    /// The checkpoint contains the modulus n of the public key, the running product
+    /// and the number of accumulated ciphertexts.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n_bytes = to_asn1_bytes(&self.public_key.n);
+        let n_asn1 = Asn1BigUint::new(&n_bytes);
+
+        let product_bytes = to_asn1_bytes(&self.product);
+        let product_asn1 = Asn1BigUint::new(&product_bytes);
+
+        // Write all elements to ASN.1 Sequence
+        let result = asn1::write(|w| {
+            w.write_element(&asn1::SequenceWriter::new(&|w| {
+                w.write_element(&n_asn1)?; // Add n to the sequence
+                w.write_element(&product_asn1)?; // Add the running product to the sequence
+                w.write_element(&self.count)?; // Add the count to the sequence
+                Ok(())
+            }))
+        });
+
+        result.unwrap_or_else(|_| vec![])
+    }
+
+    /// Resumes an accumulator from a checkpoint produced by `to_bytes`.
+    /// Returns an `InvalidKey` error if the checkpoint was made under another modulus.
+    pub fn from_bytes(bytes: &[u8], public_key: &PublicKey) -> Result<Self, OkamotoUchiyamaError> {
+        // Parse the ASN.1 sequence
+        let (n, product, count) =
+            asn1::parse::<_, asn1::ParseError, _>(bytes, |d: &mut asn1::Parser<'_>| {
+                d.read_element::<asn1::Sequence>()?
+                    .parse::<_, asn1::ParseError, _>(|d| {
+                        let n_asn1 = d.read_element::<Asn1BigUint>()?;
+                        let product_asn1 = d.read_element::<Asn1BigUint>()?;
+                        let count = d.read_element::<u64>()?;
+
+                        // Convert ASN.1 BigUint to BigUint
+                        let n = BigUint::from_bytes_be(n_asn1.as_bytes());
+                        let product = BigUint::from_bytes_be(product_asn1.as_bytes());
+
+                        Ok((n, product, count))
+                    })
+            })
+            .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+
+        // The checkpoint must belong to the same key
+        if n != public_key.n {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+        if product >= n {
+            return Err(OkamotoUchiyamaError::DecodingError);
+        }
+
+        Ok(HomomorphicAccumulator {
+            public_key: public_key.clone(),
+            product,
+            count,
+            rerandomize_every: 0,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+
+use num::One;
+use num_bigint_dig::BigUint;
+
+/// A public key carrying a public bound on the plaintext space.
+/// Decryption is only correct for messages smaller than p, which the public key
+/// alone cannot check. The bound is a safe lower bound on p, set during key
+/// generation, so messages below it always decrypt correctly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BoundedPublicKey {
+    // The underlying public key
+    pub public_key: PublicKey,
+    // Exclusive upper bound for the messages, lower than or equal to p
+    pub plaintext_bound: BigUint,
+}
+
+impl BoundedPublicKey {
+    /// Creates a bounded public key from a public key and a plaintext bound.
+    /// The bound must not exceed p for the guarantee to hold.
+    pub fn new(public_key: &PublicKey, plaintext_bound: &BigUint) -> Self {
+        BoundedPublicKey {
+            public_key: public_key.clone(),
+            plaintext_bound: plaintext_bound.clone(),
+        }
+    }
+
+    // Creates a bounded public key using 2^(bits(p) - 1), which is at most p, as the bound
+    pub(crate) fn from_p(public_key: PublicKey, p: &BigUint) -> Self {
+        let plaintext_bound = BigUint::one() << (p.bits() - 1);
+        BoundedPublicKey {
+            public_key,
+            plaintext_bound,
+        }
+    }
+
+    /// Encrypts a message, returning a `MessageTooLarge` error if it is not below the bound
+    pub fn encrypt(&self, message: &BigUint) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        if message >= &self.plaintext_bound {
+            return Err(OkamotoUchiyamaError::MessageTooLarge);
+        }
+
+        Ok(OkamotoUchiyama::encrypt(message, &self.public_key))
+    }
+
+    /// Performs the homomorphic addition of two ciphertexts, refusing it if the sum could
+    /// wrap past p.
+    /// Budget model: the caller asserts that the plaintext of each operand is below
+    /// `max_plaintext`, so their sum is below 2 * max_plaintext. The addition is only
+    /// performed when 2 * max_plaintext is below the plaintext bound, which is itself at
+    /// most p, otherwise a `PlaintextOverflow` error is returned.
+    /// The assertion on the operands cannot be checked without the private key.
+    pub fn homomorphic_encrypt_two_checked(
+        &self,
+        c1: &Ciphertext,
+        c2: &Ciphertext,
+        max_plaintext: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        if max_plaintext * 2u32 >= self.plaintext_bound {
+            return Err(OkamotoUchiyamaError::PlaintextOverflow);
+        }
+
+        self.public_key.homomorphic_encrypt_two(c1, c2)
+    }
+}
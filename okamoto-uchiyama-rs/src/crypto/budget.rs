@@ -0,0 +1,30 @@
+use num_bigint_dig::BigUint;
+use std::fmt;
+
+/// The plaintext-space budget of a private key, for aggregation planning, see
+/// `PrivateKey::budget_report`.
+/// Okamoto-Uchiyama ciphertexts carry no growing noise: the only budget is the plaintext
+/// space Z/pZ, which any sum or product of plaintexts wraps around once it reaches p.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BudgetReport {
+    // Bit size of p, the plaintext modulus
+    pub plaintext_modulus_bits: u64,
+    // Largest message decrypting to itself, p - 1
+    pub max_message: BigUint,
+    // Bound on every term the report was computed for
+    pub max_term: BigUint,
+    // Number of terms up to `max_term` whose sum stays below p, see
+    // `PrivateKey::max_safe_additions`
+    pub max_additions: BigUint,
+}
+
+// Implements Display trait for the BudgetReport struct
+impl fmt::Display for BudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BudgetReport {{\n  plaintext_modulus_bits: {},\n  max_message: {},\n  max_term: {},\n  max_additions: {}\n}}",
+            self.plaintext_modulus_bits, self.max_message, self.max_term, self.max_additions
+        )
+    }
+}
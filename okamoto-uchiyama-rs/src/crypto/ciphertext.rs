@@ -1,25 +1,75 @@
+use crate::crypto::public_key::PublicKey;
+use crate::crypto::sealed_ciphertext::SealedCiphertext;
 use crate::error::OkamotoUchiyamaError;
 
-use crate::pem::PemEncodable;
+use crate::pem::{
+    check_decoded_size, read_detected, to_asn1_bytes, verify_checksum, Detected, PemEncodable,
+    DEFAULT_MAX_PEM_BYTES,
+};
 use asn1::BigUint as Asn1BigUint;
 use base64::engine::general_purpose;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
+use num::{One, Zero};
 use num_bigint_dig::BigUint;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Read;
 
-// Define a Ciphertext struct to encapsulate a ciphertext value
-#[derive(Debug, Clone, PartialEq, Eq)]
+// Number of plaintexts m tried by `is_likely_unrandomized`
+const UNRANDOMIZED_SEARCH_RANGE: u32 = 1 << 16;
+
+// Define a Ciphertext struct to encapsulate a ciphertext value.
+// Equality only compares the value: a tagged and an untagged ciphertext of the same value
+// are equal.
+#[derive(Debug, Clone)]
 pub struct Ciphertext {
     value: BigUint,
+    // Fingerprint of the public key the ciphertext was encrypted with, if tagged
+    key_id: Option<[u8; 32]>,
+}
+
+impl PartialEq for Ciphertext {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
 }
 
+impl Eq for Ciphertext {}
+
 impl Ciphertext {
     // Constructor function to create a new Ciphertext instance
     pub fn new(value: BigUint) -> Self {
-        Ciphertext { value }
+        Ciphertext {
+            value,
+            key_id: None,
+        }
+    }
+
+    /// Creates a ciphertext tagged with the fingerprint of its originating public key.
+    /// The tag is not part of the PEM encoding.
+    pub fn tagged(value: BigUint, key_id: [u8; 32]) -> Self {
+        Ciphertext {
+            value,
+            key_id: Some(key_id),
+        }
     }
 
     /// Attempt to create a `Ciphertext` from a PEM-encoded string
+    /// The decoded body is limited to `DEFAULT_MAX_PEM_BYTES` bytes
     pub fn from_pem(pem: &str) -> Result<Self, OkamotoUchiyamaError> {
+        Self::from_pem_limited(pem, DEFAULT_MAX_PEM_BYTES)
+    }
+
+    /// Same as `from_pem`, but expects a PEM string produced by `to_pem_checked` and
+    /// returns a `ChecksumMismatch` error if its content does not match the checksum
+    pub fn from_pem_checked(pem: &str) -> Result<Self, OkamotoUchiyamaError> {
+        Self::from_pem(&verify_checksum(pem)?)
+    }
+
+    /// Same as `from_pem`, but rejects inputs whose decoded body exceeds `max_bytes`
+    /// with an `InputTooLarge` error, before decoding them
+    pub fn from_pem_limited(pem: &str, max_bytes: usize) -> Result<Self, OkamotoUchiyamaError> {
         // Trim the starting and ending spaces/newlines
         let pem = pem.trim();
 
@@ -27,7 +77,7 @@ impl Ciphertext {
         if !pem.starts_with("-----BEGIN CIPHERTEXT-----")
             || !pem.ends_with("-----END CIPHERTEXT-----")
         {
-            return Err(OkamotoUchiyamaError::PemDecodingError);
+            return Err(OkamotoUchiyamaError::PemDecodingError(None));
         }
 
         // Extract the base64-encoded ASN.1 sequence between the tags
@@ -36,18 +86,140 @@ impl Ciphertext {
             .trim_end_matches("-----END CIPHERTEXT-----")
             .trim();
 
+        // Reject oversized inputs before decoding them
+        check_decoded_size(base64_encoded, max_bytes)?;
+
         // Decode the base64-encoded ASN.1 sequence using Engine::decode
         let asn1_decoded = general_purpose::STANDARD
             .decode(base64_encoded.as_bytes())
-            .map_err(|_| OkamotoUchiyamaError::PemDecodingError)?;
+            .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+
+        Self::from_der(&asn1_decoded).map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))
+    }
+
+    /// Loads a ciphertext from a reader, detecting whether it holds a PEM string (checked
+    /// or not) or raw DER bytes, with the same heuristics as `PublicKey::load`, except
+    /// that the DER encoding of a ciphertext starts with the INTEGER tag 0x02.
+    /// Base64url strings are not detected, use `from_base64url` for them.
+    pub fn load(reader: impl Read) -> Result<Self, OkamotoUchiyamaError> {
+        match read_detected(reader)? {
+            Detected::Pem(pem) => Self::from_pem(&pem),
+            Detected::CheckedPem(pem) => Self::from_pem_checked(&pem),
+            Detected::Der(der) => Self::from_der(&der),
+        }
+    }
+
+    /// Encodes the ciphertext as unpadded base64url DER bytes, without PEM armor,
+    /// for compact URL-safe transport
+    pub fn to_base64url(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.to_der())
+    }
+
+    /// Decodes a ciphertext encoded by `to_base64url`.
+    /// The decoded bytes are limited to `DEFAULT_MAX_PEM_BYTES` bytes
+    pub fn from_base64url(encoded: &str) -> Result<Self, OkamotoUchiyamaError> {
+        // Reject oversized inputs before decoding them
+        check_decoded_size(encoded, DEFAULT_MAX_PEM_BYTES)?;
+
+        let asn1_decoded = URL_SAFE_NO_PAD
+            .decode(encoded.trim())
+            .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+
+        Self::from_der(&asn1_decoded)
+    }
+
+    /// Encodes ciphertexts, in order, into a single `CIPHERTEXT BUNDLE` PEM string whose
+    /// body is a DER SEQUENCE of INTEGERs. Key tags are not part of the encoding.
+    pub fn bundle_to_pem(ciphertexts: &[Ciphertext]) -> String {
+        let values: Vec<Vec<u8>> = ciphertexts
+            .iter()
+            .map(|c| to_asn1_bytes(&c.value))
+            .collect();
+
+        // Write every value to an ASN.1 Sequence
+        let der = asn1::write(|w| {
+            w.write_element(&asn1::SequenceWriter::new(&|w| {
+                for value in &values {
+                    w.write_element(&Asn1BigUint::new(value))?;
+                }
+                Ok(())
+            }))
+        })
+        .unwrap_or_else(|_| vec![]);
+
+        let mut pem = String::new();
+        pem.push_str("-----BEGIN CIPHERTEXT BUNDLE-----\n");
+        pem.push_str(&general_purpose::STANDARD.encode(der));
+        pem.push_str("\n-----END CIPHERTEXT BUNDLE-----\n");
+
+        pem
+    }
+
+    /// Decodes the ciphertexts of a `CIPHERTEXT BUNDLE` PEM string produced by
+    /// `bundle_to_pem`, in order.
+    /// The decoded body is limited to `DEFAULT_MAX_PEM_BYTES` bytes
+    pub fn bundle_from_pem(pem: &str) -> Result<Vec<Self>, OkamotoUchiyamaError> {
+        // Trim the starting and ending spaces/newlines
+        let pem = pem.trim();
+
+        // Check if the PEM string starts and ends with the correct tags
+        if !pem.starts_with("-----BEGIN CIPHERTEXT BUNDLE-----")
+            || !pem.ends_with("-----END CIPHERTEXT BUNDLE-----")
+        {
+            return Err(OkamotoUchiyamaError::PemDecodingError(None));
+        }
+
+        // Extract the base64-encoded ASN.1 sequence between the tags
+        let base64_encoded = pem
+            .trim_start_matches("-----BEGIN CIPHERTEXT BUNDLE-----")
+            .trim_end_matches("-----END CIPHERTEXT BUNDLE-----")
+            .trim();
+
+        // Reject oversized inputs before decoding them
+        check_decoded_size(base64_encoded, DEFAULT_MAX_PEM_BYTES)?;
+
+        let asn1_decoded = general_purpose::STANDARD
+            .decode(base64_encoded.as_bytes())
+            .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+
+        // Parse the ASN.1 sequence into a list of ciphertexts
+        asn1::parse::<_, asn1::ParseError, _>(&asn1_decoded, |d: &mut asn1::Parser<'_>| {
+            d.read_element::<asn1::Sequence>()?
+                .parse::<_, asn1::ParseError, _>(|d| {
+                    let mut ciphertexts = Vec::new();
+                    while !d.is_empty() {
+                        let value_asn1 = d.read_element::<Asn1BigUint>()?;
+                        ciphertexts.push(Ciphertext::new(BigUint::from_bytes_be(
+                            value_asn1.as_bytes(),
+                        )));
+                    }
+                    Ok(ciphertexts)
+                })
+        })
+        .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))
+    }
+
+    /// Encodes the ciphertext value as a DER INTEGER, without its key tag
+    pub fn to_der(&self) -> Vec<u8> {
+        // Convert the ciphertext value to ASN.1
+        let value_bytes = to_asn1_bytes(&self.value);
+        let value_asn1 = Asn1BigUint::new(&value_bytes);
 
+        // Write the value to ASN.1
+        let result = asn1::write(|w| w.write_element(&value_asn1));
+        result.unwrap_or_else(|_| vec![])
+    }
+
+    /// Decodes an untagged ciphertext from a DER INTEGER produced by `to_der`,
+    /// returning a `DecodingError` error if it is malformed
+    pub fn from_der(der: &[u8]) -> Result<Self, OkamotoUchiyamaError> {
         // Parse the ASN.1 sequence into a BigUint
-        let value_asn1 = asn1::parse_single::<Asn1BigUint>(&asn1_decoded)
-            .map_err(|_| OkamotoUchiyamaError::PemDecodingError)?;
+        let value_asn1 = asn1::parse_single::<Asn1BigUint>(der)
+            .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
 
         // Convert the ASN.1 BigUint to a BigUint
         let value_bytes = value_asn1.as_bytes();
-        let value = BigUint::from_bytes_be(&value_bytes);
+        let value = BigUint::from_bytes_be(value_bytes);
 
         // Return a new `Ciphertext` instance
         Ok(Ciphertext::new(value))
@@ -57,6 +229,171 @@ impl Ciphertext {
     pub fn value(&self) -> &BigUint {
         &self.value
     }
+
+    /// Returns the ciphertext value as little-endian bytes, with the same layout as
+    /// `PublicKey::n_le_bytes`
+    pub fn value_le_bytes(&self) -> Vec<u8> {
+        self.value.to_bytes_le()
+    }
+
+    /// Returns the bit length of the ciphertext value
+    pub fn bit_length(&self) -> u64 {
+        self.value.bits() as u64
+    }
+
+    /// Heuristically checks whether the ciphertext looks malformed for the given key.
+    /// A proper ciphertext is close to uniform in [1, n - 1], so its bit length is below
+    /// that of n by more than 64 bits (or half of it for tiny keys) with negligible
+    /// probability; a smaller value hints at a missing randomness. Values not below n are
+    /// suspicious as well. A false result does not prove the ciphertext is well-formed.
+    pub fn looks_suspicious(&self, public_key: &PublicKey) -> bool {
+        let n_bits = public_key.n.bits() as u64;
+        let slack = (n_bits / 2).min(64);
+        self.value >= public_key.n || self.bit_length() + slack < n_bits
+    }
+
+    /// Heuristically checks whether the ciphertext was formed without randomness, i.e. equals
+    /// g^m mod n (r = 0) for a small m, which reveals m to anyone holding the public key.
+    /// It tries every m below 2^16 by brute force, one modular multiplication each, so an
+    /// unrandomized encryption of a larger plaintext is not detected.
+    pub fn is_likely_unrandomized(&self, public_key: &PublicKey) -> bool {
+        if public_key.n.is_zero() {
+            return false;
+        }
+
+        let value = &self.value % &public_key.n;
+        let mut power = BigUint::one() % &public_key.n;
+        for _ in 0..UNRANDOMIZED_SEARCH_RANGE {
+            if power == value {
+                return true;
+            }
+            power = (power * &public_key.g) % &public_key.n;
+        }
+        false
+    }
+
+    /// Binds the ciphertext to a public key to chain homomorphic operations fluently,
+    /// e.g. `c.with_key(&pk).add(&c2)?.mul_const(&k)?.add_const(&j)?.finish()`
+    pub fn with_key<'a>(&self, public_key: &'a PublicKey) -> KeyedCiphertext<'a> {
+        KeyedCiphertext {
+            public_key,
+            ciphertext: self.clone(),
+        }
+    }
+
+    /// Adds the plaintext of `other` to the plaintext of this ciphertext in place, i.e.
+    /// multiplies its value by `other` mod n, without allocating a new ciphertext.
+    /// Both ciphertexts are validated under the public key, and the result is tagged if
+    /// either of them is. On error, the ciphertext is left unchanged.
+    pub fn add_assign_with_key(
+        &mut self,
+        other: &Ciphertext,
+        public_key: &PublicKey,
+    ) -> Result<(), OkamotoUchiyamaError> {
+        public_key.check_ciphertexts([&*self, other])?;
+
+        self.value *= &other.value;
+        self.value %= &public_key.n;
+        if other.key_id.is_some() && self.key_id.is_none() {
+            self.key_id = other.key_id;
+        }
+        Ok(())
+    }
+
+    /// Selects `a` if `choice` is 0 and `b` if it is 1, in constant time.
+    /// Both values are padded to the same byte length and selected byte by byte without
+    /// branching on `choice`. The tag is kept only if both ciphertexts share it.
+    #[cfg(feature = "subtle")]
+    pub fn conditional_select(
+        a: &Ciphertext,
+        b: &Ciphertext,
+        choice: subtle::Choice,
+    ) -> Ciphertext {
+        use subtle::ConditionallySelectable;
+
+        // Left-pad both values to the same byte length
+        let a_bytes = a.value.to_bytes_be();
+        let b_bytes = b.value.to_bytes_be();
+        let len = a_bytes.len().max(b_bytes.len());
+        let pad = |bytes: Vec<u8>| {
+            let mut padded = vec![0u8; len - bytes.len()];
+            padded.extend_from_slice(&bytes);
+            padded
+        };
+        let (a_bytes, b_bytes) = (pad(a_bytes), pad(b_bytes));
+
+        let selected: Vec<u8> = a_bytes
+            .iter()
+            .zip(&b_bytes)
+            .map(|(a_byte, b_byte)| u8::conditional_select(a_byte, b_byte, choice))
+            .collect();
+
+        Ciphertext {
+            value: BigUint::from_bytes_be(&selected),
+            key_id: if a.key_id == b.key_id { a.key_id } else { None },
+        }
+    }
+
+    /// Seals the ciphertext with an HMAC-SHA256 tag over its DER encoding, for
+    /// tamper-evidence at rest, see `SealedCiphertext`
+    pub fn seal(&self, mac_key: &[u8]) -> SealedCiphertext {
+        SealedCiphertext::new(self, mac_key)
+    }
+
+    /// Returns the fingerprint of the ciphertext, the SHA-256 digest of its DER encoding
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.to_der()).into()
+    }
+
+    /// Returns the fingerprint of the originating public key, if the ciphertext is tagged
+    pub fn key_id(&self) -> Option<&[u8; 32]> {
+        self.key_id.as_ref()
+    }
+}
+
+/// A ciphertext bound to a public key, chaining homomorphic operations.
+/// Each step validates its operands and reduces the result mod n.
+#[derive(Debug, Clone)]
+pub struct KeyedCiphertext<'a> {
+    // The public key the ciphertext is encrypted with
+    public_key: &'a PublicKey,
+    // The current result of the chain
+    ciphertext: Ciphertext,
+}
+
+impl KeyedCiphertext<'_> {
+    /// Adds the plaintext of another ciphertext
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, other: &Ciphertext) -> Result<Self, OkamotoUchiyamaError> {
+        let ciphertext = self
+            .public_key
+            .homomorphic_encrypt_two(&self.ciphertext, other)?;
+        Ok(KeyedCiphertext { ciphertext, ..self })
+    }
+
+    /// Multiplies the plaintext by a public constant
+    pub fn mul_const(self, k: &BigUint) -> Result<Self, OkamotoUchiyamaError> {
+        let ciphertext = self.public_key.multiply_constant(&self.ciphertext, k)?;
+        Ok(KeyedCiphertext { ciphertext, ..self })
+    }
+
+    /// Adds a public constant to the plaintext
+    pub fn add_const(self, j: &BigUint) -> Result<Self, OkamotoUchiyamaError> {
+        let constant = self.public_key.encrypt_constant(j);
+        self.add(&constant)
+    }
+
+    /// Ends the chain and returns the resulting ciphertext
+    pub fn finish(self) -> Ciphertext {
+        self.ciphertext
+    }
+}
+
+// Implements Display trait for the Ciphertext struct
+impl fmt::Display for Ciphertext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ciphertext {{\n  value: {}\n}}", self.value)
+    }
 }
 
 /// Implement the PemEncodable trait for the Ciphertext struct
@@ -64,16 +401,9 @@ impl PemEncodable for Ciphertext {
     fn to_pem(&self) -> String {
         let mut pem = String::new();
 
-        // Convert the ciphertext value to ASN.1
-        let value_bytes = self.value.to_bytes_be();
-        let value_asn1 = Asn1BigUint::new(&value_bytes);
-
-        // Write the value to ASN.1 Sequence
-        let result = asn1::write(|w| w.write_element(&value_asn1));
-
-        // Encode the ASN.1 sequence using Base64
+        // Encode the ASN.1 value using Base64
         pem.push_str("-----BEGIN CIPHERTEXT-----\n");
-        pem.push_str(&general_purpose::STANDARD.encode(result.unwrap_or_else(|_| vec![])));
+        pem.push_str(&general_purpose::STANDARD.encode(self.to_der()));
         pem.push_str("\n-----END CIPHERTEXT-----\n");
 
         pem
@@ -0,0 +1,73 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+
+use num::Zero;
+use num_bigint_dig::BigUint;
+use std::collections::HashMap;
+
+/// An encrypted key-value counter store.
+/// Each counter is a ciphertext adjusted homomorphically, so the store can be kept by a
+/// party without the private key; the counters are read with `PrivateKey::read_counters`.
+/// A counter starts at zero the first time it is adjusted, and every adjustment
+/// re-randomizes it, so successive ciphertexts do not reveal the adjustments. Counters
+/// live in Z/pZ: decrementing below zero wraps around to p - |value|.
+#[derive(Debug, Clone)]
+pub struct EncryptedCounters<'a> {
+    // The public key the counters are encrypted with
+    public_key: &'a PublicKey,
+    // The encrypted counters by name
+    counters: HashMap<String, Ciphertext>,
+}
+
+impl<'a> EncryptedCounters<'a> {
+    /// Creates an empty counter store for the given public key
+    pub fn new(public_key: &'a PublicKey) -> Self {
+        EncryptedCounters {
+            public_key,
+            counters: HashMap::new(),
+        }
+    }
+
+    /// Adds `by` to a counter, i.e. multiplies it by g^by mod n
+    pub fn increment(&mut self, key: &str, by: u64) -> Result<(), OkamotoUchiyamaError> {
+        let factor = self.public_key.encrypt_constant(&BigUint::from(by));
+        self.adjust(key, factor.value())
+    }
+
+    /// Subtracts `by` from a counter, i.e. multiplies it by g^(-by) mod n.
+    /// Returns an `InvalidKey` error if g is not invertible mod n.
+    pub fn decrement(&mut self, key: &str, by: u64) -> Result<(), OkamotoUchiyamaError> {
+        let inv_g = self.public_key.inv_g_mod_n()?;
+        let factor = inv_g.modpow(&BigUint::from(by), &self.public_key.n);
+        self.adjust(key, &factor)
+    }
+
+    /// Returns the encrypted counter of a key, if it was ever adjusted
+    pub fn get(&self, key: &str) -> Option<&Ciphertext> {
+        self.counters.get(key)
+    }
+
+    /// Returns every encrypted counter by name
+    pub fn counters(&self) -> &HashMap<String, Ciphertext> {
+        &self.counters
+    }
+
+    // Multiplies a counter by a factor and a fresh encryption of zero mod n, creating it if
+    // absent. Without the encryption of zero, the ratio of two successive ciphertexts would
+    // be g^(+-by), from which anyone could recover a small adjustment.
+    fn adjust(&mut self, key: &str, factor: &BigUint) -> Result<(), OkamotoUchiyamaError> {
+        let n = &self.public_key.n;
+        let zero = OkamotoUchiyama::encrypt(&BigUint::zero(), self.public_key);
+        let mut value = (zero.value() * factor) % n;
+        if let Some(counter) = self.counters.get(key) {
+            self.public_key.check_ciphertext(counter)?;
+            value = (value * counter.value()) % n;
+        }
+
+        self.counters
+            .insert(key.to_string(), Ciphertext::new(value));
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::private_key::PrivateKey;
+use crate::error::OkamotoUchiyamaError;
+
+use num_bigint_dig::BigUint;
+use std::time::{Duration, Instant};
+
+/// A private key metering its decryptions, to slow down attacks querying a decryption oracle.
+/// At most `max_decryptions` decryptions are allowed per period, further ones fail with a
+/// `RateLimited` error until the next period starts.
+/// This is a blunt instrument: it bounds the number of oracle queries, but it does not make
+/// the scheme secure against chosen-ciphertext attacks, which Okamoto-Uchiyama is not.
+#[derive(Debug, Clone)]
+pub struct DecryptionGuard<'a> {
+    // The guarded private key
+    private_key: &'a PrivateKey,
+    // Number of decryptions allowed per period
+    max_decryptions: u64,
+    // Length of a period
+    period: Duration,
+    // Start of the current period
+    period_start: Instant,
+    // Number of decryptions performed during the current period
+    count: u64,
+}
+
+impl<'a> DecryptionGuard<'a> {
+    /// Guards a private key, allowing `max_decryptions` decryptions per `period`
+    pub fn new(private_key: &'a PrivateKey, max_decryptions: u64, period: Duration) -> Self {
+        DecryptionGuard {
+            private_key,
+            max_decryptions,
+            period,
+            period_start: Instant::now(),
+            count: 0,
+        }
+    }
+
+    /// Decrypts a ciphertext, returning a `RateLimited` error if the decryptions of the
+    /// current period are exhausted. Rejected ciphertexts do not count.
+    pub fn decrypt(&mut self, c: &Ciphertext) -> Result<BigUint, OkamotoUchiyamaError> {
+        // Start a new period once the current one is over
+        if self.period_start.elapsed() >= self.period {
+            self.period_start = Instant::now();
+            self.count = 0;
+        }
+
+        if self.count >= self.max_decryptions {
+            return Err(OkamotoUchiyamaError::RateLimited);
+        }
+        self.private_key.public_key.check_ciphertext(c)?;
+
+        self.count += 1;
+        Ok(OkamotoUchiyama::decrypt(c, self.private_key))
+    }
+
+    /// Returns the number of decryptions left in the current period
+    pub fn remaining(&self) -> u64 {
+        if self.period_start.elapsed() >= self.period {
+            return self.max_decryptions;
+        }
+        self.max_decryptions.saturating_sub(self.count)
+    }
+}
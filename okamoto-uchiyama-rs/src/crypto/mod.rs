@@ -1,4 +1,19 @@
+pub mod accumulator;
+pub mod bounded_public_key;
+pub mod budget;
 pub mod ciphertext;
+pub mod counters;
+pub mod decryption_guard;
+pub mod multikey;
 pub mod okamoto_uchiyama;
+pub mod parameters;
 pub mod private_key;
+pub mod provenance;
 pub mod public_key;
+pub mod receipt;
+pub mod sealed_ciphertext;
+pub mod session;
+pub mod signed_ciphertext;
+pub mod subtraction_context;
+pub mod tables;
+pub mod transcript;
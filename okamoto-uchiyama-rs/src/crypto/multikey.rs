@@ -0,0 +1,38 @@
+use crate::crypto::public_key::invert_mod;
+use crate::error::OkamotoUchiyamaError;
+
+use num::{One, Zero};
+use num_bigint_dig::BigUint;
+
+/// Combines plaintexts decrypted under independent keys with the Chinese remainder theorem.
+/// Each pair holds a plaintext mod p_i, e.g. a partial result decrypted with a key whose
+/// prime is p_i, and p_i itself. Returns the unique value x in [0, prod p_i) such that
+/// x = plaintext_i mod p_i for every pair.
+/// The moduli must be pairwise coprime, which distinct primes always are: a
+/// `ModuliNotCoprime` error is returned otherwise, and an `InvalidDivisor` error if a modulus
+/// is zero. Residues are reduced mod their modulus first. An empty slice combines to zero.
+pub fn combine_plaintexts_crt(
+    results: &[(BigUint, BigUint)],
+) -> Result<BigUint, OkamotoUchiyamaError> {
+    // Invariant: x is the combination of the pairs seen so far, mod their product m
+    let mut x = BigUint::zero();
+    let mut m = BigUint::one();
+
+    for (residue, modulus) in results {
+        if modulus.is_zero() {
+            return Err(OkamotoUchiyamaError::InvalidDivisor);
+        }
+        // Every value is congruent mod 1
+        if modulus.is_one() {
+            continue;
+        }
+
+        // Lift x to the residue mod p_i: x + m * ((r - x) * m^(-1) mod p_i)
+        let m_inverse = invert_mod(&(&m % modulus), modulus)
+            .map_err(|_| OkamotoUchiyamaError::ModuliNotCoprime)?;
+        let difference = (residue % modulus + modulus - &x % modulus) % modulus;
+        x += &m * ((difference * m_inverse) % modulus);
+        m *= modulus;
+    }
+    Ok(x)
+}
@@ -1,14 +1,40 @@
-use crate::key::KeySize;
+use crate::key::{KeyGenOptions, KeySize, MILLER_RABIN_ROUNDS, MIN_PRIME_BITS};
 
-use num::One;
+use num::{One, Zero};
 use num_bigint_dig::algorithms::mod_inverse;
+use num_bigint_dig::prime::probably_prime;
 use num_bigint_dig::{BigUint, RandBigInt};
 use num_primes::Generator;
-use rand::thread_rng;
+use rand::rngs::ThreadRng;
+use rand::{thread_rng, CryptoRng, Rng, RngCore};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, Write};
 
+use crate::crypto::bounded_public_key::BoundedPublicKey;
 use crate::crypto::ciphertext::Ciphertext;
-pub use crate::crypto::private_key::PrivateKey;
+use crate::crypto::parameters::ParameterSet;
+pub use crate::crypto::private_key::{DecryptionStrategy, PrivateKey};
+use crate::crypto::provenance::{KeyProvenance, PrimeSource};
 pub use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+
+/// How `OkamotoUchiyama::encrypt_with_policy` handles messages not below n.
+/// Only plaintexts below p decrypt to themselves: any message m decrypts to m mod p, and
+/// since p divides n, reducing m mod n or mod p first does not change that. The policies
+/// differ in which messages they accept and in the size of the exponent of g.
+#[derive(Debug, Clone, Copy)]
+pub enum EncryptPolicy<'a> {
+    // Reject messages not below n with a `MessageTooLarge` error. Messages in [p, n) are
+    // still accepted and silently decrypt to m mod p, as the public key does not know p:
+    // use `BoundedPublicKey::encrypt` to reject every message not below p instead
+    Strict,
+    // Encrypt m mod n, which bounds the cost of the exponentiation of oversized messages
+    ReduceModN,
+    // Encrypt m mod p, as `PublicKey::encrypt_reduced`: every message decrypts to m mod p.
+    // Reducing needs p, hence the private key, which must match the public key or the
+    // encryption fails with a `KeyMismatch` error
+    ReduceModP(&'a PrivateKey),
+}
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Default)]
@@ -29,29 +55,137 @@ pub struct OkamotoUchiyama {
     pub h: BigUint,
     // The length in bits of the Okamoto-Uchiyama public key modulus.
     pub length: u32,
+    // How the cryptosystem was generated, free of secret material
+    pub provenance: KeyProvenance,
 }
 
 impl OkamotoUchiyama {
     #[allow(unused)]
     // Init the cryptosystem by generating the constants used for key-pair creation
-    pub fn init(key_size: KeySize) -> Self {
-        // Select the key size
-        let length = match key_size {
-            KeySize::Bits512 => 512,
-            KeySize::Bits1024 => 1024,
-            KeySize::Bits2048 => 2048,
-            KeySize::Bits4096 => 4096,
-        };
+    // Returns an `InvalidKeySize` error if the key size is too small
+    pub fn init(key_size: KeySize) -> Result<Self, OkamotoUchiyamaError> {
+        // Select the key size and the bit sizes of the primes
+        let length = key_size.bits();
+        let (p_bits, q_bits) = key_size.prime_sizes()?;
 
-        // Calculate a large prime number with `length / 3` bit length
-        let p_prime = Generator::new_prime((length / 3) as usize);
-        // Convert the prime number to BigUint
-        let p = BigUint::from_bytes_be(&p_prime.to_bytes_be());
+        Ok(Self::generate(length, p_bits, q_bits))
+    }
 
-        // Calculate another large prime number with `length / 2` bit length
-        let q_prime = Generator::new_prime((length / 2) as usize);
-        // Convert the prime number to BigUint
-        let q = BigUint::from_bytes_be(&q_prime.to_bytes_be());
+    /// Init the cryptosystem as `init`, but with g derived from n by `derive_g` instead of
+    /// drawn with `thread_rng`, so that g is a deterministic function of n and of the
+    /// counter of the first accepted candidate, and can be audited.
+    /// Returns an `InvalidKeySize` error if the key size is too small
+    pub fn init_with_derived_g(key_size: KeySize) -> Result<Self, OkamotoUchiyamaError> {
+        let length = key_size.bits();
+        let (p_bits, q_bits) = key_size.prime_sizes()?;
+
+        let (p, q) = Self::generate_primes(p_bits, q_bits);
+        let (g, _) = Self::derive_g(&(&p * &p * &q), &p);
+
+        Ok(Self::assemble(length, p, q, g, None))
+    }
+
+    /// Derives g from n: candidates `g_candidate(n, counter)` are tried for counter = 0, 1, ...
+    /// and the first one satisfying g^(p-1) mod p^2 != 1 is returned with its counter.
+    /// Checking the condition needs p, but anyone can check that a published g equals the
+    /// candidate of its counter.
+    pub fn derive_g(n: &BigUint, p: &BigUint) -> (BigUint, u32) {
+        let p_squared = p * p;
+        let p_minus_1 = p - 1u32;
+
+        let mut counter = 0u32;
+        loop {
+            let g = Self::g_candidate(n, counter);
+            if !g.modpow(&p_minus_1, &p_squared).is_one() {
+                return (g, counter);
+            }
+            counter += 1;
+        }
+    }
+
+    /// Returns the candidate g of the given counter, in [2, n - 1]: the concatenation of the
+    /// SHA-256 digests of `n || counter || block` for block = 0, 1, ..., 16 bytes longer than
+    /// n to make the bias negligible, reduced into [2, n - 1]. n and the counter are
+    /// big-endian, the counter and the block being 4 bytes long. Returns zero if n < 3.
+    pub fn g_candidate(n: &BigUint, counter: u32) -> BigUint {
+        if n < &BigUint::from(3u32) {
+            return BigUint::zero();
+        }
+
+        let n_bytes = n.to_bytes_be();
+        let mut digest = Vec::new();
+        let mut block = 0u32;
+        while digest.len() < n_bytes.len() + 16 {
+            let mut hasher = Sha256::new();
+            hasher.update(&n_bytes);
+            hasher.update(counter.to_be_bytes());
+            hasher.update(block.to_be_bytes());
+            digest.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+
+        BigUint::from_bytes_be(&digest) % (n - 2u32) + 2u32
+    }
+
+    /// Init the cryptosystem with p sized at `p_fraction * total_bits` bits and q filling
+    /// the remainder of n = p^2 * q, i.e. `total_bits - 2 * p_bits` bits.
+    /// A larger p widens the plaintext space but shrinks q; since n is easier to factor
+    /// when either prime is small, the fraction must keep both primes well-sized.
+    /// Returns an `InvalidKeySize` error if the fraction is not in (0, 0.5), and an
+    /// `InsecureKeySize` error if the resulting split fails `KeyGenOptions::validate_security`.
+    pub fn init_with_ratio(total_bits: u32, p_fraction: f64) -> Result<Self, OkamotoUchiyamaError> {
+        if !(p_fraction > 0.0 && p_fraction < 0.5) {
+            return Err(OkamotoUchiyamaError::InvalidKeySize);
+        }
+
+        let p_bits = (f64::from(total_bits) * p_fraction).round() as usize;
+        let q_bits = (total_bits as usize).saturating_sub(2 * p_bits);
+        KeyGenOptions::new(p_bits, q_bits).validate_security()?;
+
+        Ok(Self::generate(total_bits, p_bits, q_bits))
+    }
+
+    /// Init the cryptosystem with primes of the explicit bit sizes of the options.
+    /// Returns an `InsecureKeySize` error if the split fails
+    /// `KeyGenOptions::validate_security`.
+    pub fn init_with_options(options: &KeyGenOptions) -> Result<Self, OkamotoUchiyamaError> {
+        options.validate_security()?;
+
+        let length = u32::try_from(options.modulus_bits())
+            .map_err(|_| OkamotoUchiyamaError::InvalidKeySize)?;
+        Ok(Self::generate(length, options.p_bits, options.q_bits))
+    }
+
+    /// Init the cryptosystem so that n is exactly `target_bytes` bytes long, i.e.
+    /// `PublicKey::ciphertext_byte_len() == target_bytes`, for storage with fixed record sizes.
+    /// n is sized at `target_bytes * 8` bits, with p at a third of it, and primes are
+    /// generated again until the leading byte of n is non-zero.
+    /// Returns an `InvalidKeySize` error if either prime would be smaller than
+    /// `MIN_PRIME_BITS`.
+    pub fn init_for_ciphertext_bytes(target_bytes: usize) -> Result<Self, OkamotoUchiyamaError> {
+        let total_bits = target_bytes
+            .checked_mul(8)
+            .and_then(|bits| u32::try_from(bits).ok())
+            .ok_or(OkamotoUchiyamaError::InvalidKeySize)?;
+
+        let p_bits = total_bits as usize / 3;
+        let q_bits = total_bits as usize - 2 * p_bits;
+        if p_bits < MIN_PRIME_BITS || q_bits < MIN_PRIME_BITS {
+            return Err(OkamotoUchiyamaError::InvalidKeySize);
+        }
+
+        // n < 2^total_bits, so only a too short n needs to be generated again
+        loop {
+            let okamoto_uchiyama = Self::generate(total_bits, p_bits, q_bits);
+            if okamoto_uchiyama.n.bits().div_ceil(8) == target_bytes {
+                return Ok(okamoto_uchiyama);
+            }
+        }
+    }
+
+    // Generates the constants of the cryptosystem from the bit sizes of the primes
+    fn generate(length: u32, p_bits: usize, q_bits: usize) -> Self {
+        let (p, q) = Self::generate_primes(p_bits, q_bits);
 
         // Calculate n = p^2 * q
         let p_squared = &p * &p;
@@ -60,21 +194,48 @@ impl OkamotoUchiyama {
         // Find an integer `g` in the range [2, n - 1] such that g^(p-1) mod p^2 != 1
         let p_minus_1 = &p - 1u32;
         let mut rng = thread_rng();
-        let mut g = BigUint::default();
-
-        let mut gpminuse1: BigUint;
-        loop {
+        let g = loop {
             // Generate a random integer in the range [2, n - 1]
-            g = rng.gen_biguint_range(&2u32.into(), &(&n - &1u32));
+            let g = rng.gen_biguint_range(&2u32.into(), &(&n - 1u32));
             // Check if g^(p-1) mod p^2 != 1
-            gpminuse1 = g.modpow(&p_minus_1, &p_squared) % &p_squared;
-            if gpminuse1 != 0u32.into() {
-                break;
+            if !g.modpow(&p_minus_1, &p_squared).is_one() {
+                break g;
             }
-        }
+        };
+
+        Self::assemble(length, p, q, g, Some(std::any::type_name::<ThreadRng>()))
+    }
+
+    // Generates the primes p and q with the given bit sizes
+    fn generate_primes(p_bits: usize, q_bits: usize) -> (BigUint, BigUint) {
+        // Calculate a large prime number with `p_bits` bit length
+        let p_prime = Generator::new_prime(p_bits);
+        // Convert the prime number to BigUint
+        let p = BigUint::from_bytes_be(&p_prime.to_bytes_be());
+
+        // Calculate another large prime number with `q_bits` bit length
+        let q_prime = Generator::new_prime(q_bits);
+        // Convert the prime number to BigUint
+        let q = BigUint::from_bytes_be(&q_prime.to_bytes_be());
+
+        (p, q)
+    }
+
+    // Builds the cryptosystem from freshly generated primes and a g satisfying
+    // g^(p-1) mod p^2 != 1, rng naming the generator g was drawn with, if any
+    fn assemble(
+        length: u32,
+        p: BigUint,
+        q: BigUint,
+        g: BigUint,
+        rng: Option<&'static str>,
+    ) -> Self {
+        // Calculate n = p^2 * q
+        let p_squared = &p * &p;
+        let n = &p_squared * &q;
 
-        // Calculate h = g^n mod n
-        let h = g.modpow(&n, &n) % &n;
+        let gpminuse1 = g.modpow(&(&p - 1u32), &p_squared);
+        let h = Self::compute_h(&g, &n);
 
         // Return a new instance of the OkamotoUchiyama struct with the calculated values
         OkamotoUchiyama {
@@ -86,55 +247,400 @@ impl OkamotoUchiyama {
             gpminuse1,
             h,
             length,
+            provenance: KeyProvenance {
+                rng,
+                prime_source: PrimeSource::NumPrimes,
+                primality_rounds: MILLER_RABIN_ROUNDS,
+            },
         }
     }
 
-    /// Generates the public key
-    pub fn generate_public_key(&self) -> PublicKey {
-        PublicKey {
-            // Public key components
-            n: self.n.clone(),
+    /// Initializes the cryptosystem with p and q picked at random from caller-provided pools
+    /// of vetted primes instead of freshly generated ones, and g derived with the given rng.
+    /// This is meant for tests and specialized uses needing fast or fixed small keys: keys
+    /// built from a small or public pool offer no security.
+    /// Returns an `InvalidKey` error if a pool is empty, if no candidate of `q_candidates`
+    /// differs from the selected p, or if a selected candidate is not prime.
+    pub fn init_from_prime_pool<R: RngCore + CryptoRng>(
+        p_candidates: &[BigUint],
+        q_candidates: &[BigUint],
+        rng: &mut R,
+    ) -> Result<Self, OkamotoUchiyamaError> {
+        if p_candidates.is_empty() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+        let p = p_candidates[rng.gen_range(0..p_candidates.len())].clone();
+
+        // q must differ from p
+        let q_candidates: Vec<&BigUint> = q_candidates.iter().filter(|q| **q != p).collect();
+        if q_candidates.is_empty() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+        let q = q_candidates[rng.gen_range(0..q_candidates.len())].clone();
+
+        // Check the primality of p and q before searching g, which needs n > 2
+        if !is_prime(&p) || !is_prime(&q) {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        // Find an integer `g` in the range [2, n - 1] such that g^(p-1) mod p^2 != 1
+        let p_squared = &p * &p;
+        let n = &p_squared * &q;
+        let p_minus_1 = &p - 1u32;
+        let g = loop {
+            let g = rng.gen_biguint_range(&2u32.into(), &n);
+            if !g.modpow(&p_minus_1, &p_squared).is_one() {
+                break g;
+            }
+        };
+
+        let mut okamoto_uchiyama = Self::from_parameters(&ParameterSet {
+            p,
+            q,
+            g,
+            length: n.bits() as u32,
+        })?;
+        okamoto_uchiyama.provenance.rng = Some(std::any::type_name::<R>());
+        okamoto_uchiyama.provenance.prime_source = PrimeSource::Pool;
+
+        Ok(okamoto_uchiyama)
+    }
+
+    /// Returns the probability that p or q is composite despite passing the Miller-Rabin
+    /// rounds run during key generation, as recorded in `provenance`.
+    /// Each round lets a composite through with probability at most 1/4, so by the union
+    /// bound over both primes the probability is at most 2 * 4^(-rounds).
+    pub fn primality_confidence(&self) -> f64 {
+        let rounds = i32::try_from(self.provenance.primality_rounds).unwrap_or(i32::MAX);
+        (2.0 * 4f64.powi(-rounds)).min(1.0)
+    }
+
+    /// Returns how the cryptosystem was generated (RNG, prime source and primality rounds),
+    /// for audits
+    pub fn provenance(&self) -> &KeyProvenance {
+        &self.provenance
+    }
+
+    /// Exports the parameters chosen during key generation (p, q, g and the bit length of n)
+    /// for reproducibility audits.
+    /// The bit length is that of n rather than the requested key size, which n may fall short
+    /// of, so that it can be checked against p and q on import.
+    pub fn export_parameters(&self) -> ParameterSet {
+        ParameterSet {
+            p: self.p.clone(),
+            q: self.q.clone(),
             g: self.g.clone(),
-            h: self.h.clone(),
+            length: self.n.bits() as u32,
+        }
+    }
+
+    /// Rebuilds the cryptosystem from exported parameters.
+    /// Returns an `InvalidKey` error if p or q is not prime, if p == q, if the length is not
+    /// the bit length of n, or if g does not satisfy 2 <= g < n and g^(p-1) mod p^2 != 1
+    pub fn from_parameters(parameters: &ParameterSet) -> Result<Self, OkamotoUchiyamaError> {
+        let p = parameters.p.clone();
+        let q = parameters.q.clone();
+        let g = parameters.g.clone();
+
+        // Both p and q must be distinct primes
+        if p == q || !is_prime(&p) || !is_prime(&q) {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        // Calculate n = p^2 * q
+        let p_squared = &p * &p;
+        let n = &p_squared * &q;
+
+        // The length must be consistent with n
+        if n.bits() != parameters.length as usize {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        // g must be in the range [2, n - 1] and satisfy g^(p-1) mod p^2 != 1
+        if g < BigUint::from(2u32) || g >= n {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+        let gpminuse1 = g.modpow(&(&p - 1u32), &p_squared);
+        if gpminuse1.is_one() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        let h = Self::compute_h(&g, &n);
+
+        Ok(OkamotoUchiyama {
+            p,
+            p_squared,
+            q,
+            n,
+            g,
+            gpminuse1,
+            h,
+            length: parameters.length,
+            provenance: KeyProvenance {
+                rng: None,
+                prime_source: PrimeSource::Imported,
+                primality_rounds: MILLER_RABIN_ROUNDS,
+            },
+        })
+    }
+
+    /// Computes h = g^n mod n, the second generator of a public key, so that the h of a
+    /// public key can be checked independently of its generation.
+    /// Returns zero if n is zero.
+    pub fn compute_h(g: &BigUint, n: &BigUint) -> BigUint {
+        if n.is_zero() {
+            return BigUint::zero();
+        }
+        g.modpow(n, n)
+    }
+
+    /// Generates the public key
+    /// Returns an `InvalidKey` error if the cryptosystem was not initialized with `init`
+    pub fn generate_public_key(&self) -> Result<PublicKey, OkamotoUchiyamaError> {
+        // A default (uninitialized) instance has a zero modulus
+        if self.n == BigUint::default() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
         }
+
+        // Public key components
+        Ok(PublicKey::new(&self.n, &self.g, &self.h))
+    }
+
+    /// Derives a public key bound to a context, e.g. a tenant name, sharing the modulus n
+    /// of this cryptosystem: g' = g^e mod n and h' = g'^n mod n, where e is hashed from the
+    /// context with SHA-256 and is not a multiple of p, so g' still satisfies the order
+    /// condition. The same context always gives the same key.
+    /// The subkeys share the factorization of n, so `PrivateKey::new(&subkey, &p, &q)`
+    /// decrypts under any of them: they namespace encrypted data, they do not isolate
+    /// tenants from each other. Since e is public, an encryption of m under a subkey is also
+    /// an encryption of e * m under this key.
+    /// Returns an `InvalidKey` error if the cryptosystem was not initialized.
+    pub fn derive_subkey(&self, context: &[u8]) -> Result<PublicKey, OkamotoUchiyamaError> {
+        let public_key = self.generate_public_key()?;
+        let p_minus_1 = &self.p - 1u32;
+
+        let mut counter = 0u32;
+        loop {
+            let mut hasher = Sha256::new();
+            hasher.update(counter.to_be_bytes());
+            hasher.update(context);
+            let e = BigUint::from_bytes_be(&hasher.finalize());
+            counter += 1;
+
+            let g = public_key.g.modpow(&e, &self.n);
+            if (&e % &self.p).is_zero() || g.modpow(&p_minus_1, &self.p_squared).is_one() {
+                continue;
+            }
+
+            let h = Self::compute_h(&g, &self.n);
+            return Ok(PublicKey::new(&self.n, &g, &h));
+        }
+    }
+
+    /// Generates the public key along with a public plaintext bound of 2^(bits(p) - 1),
+    /// which is lower than p, so that encryptors can reject messages that would not decrypt
+    /// Returns an `InvalidKey` error if the cryptosystem was not initialized with `init`
+    pub fn generate_bounded_public_key(&self) -> Result<BoundedPublicKey, OkamotoUchiyamaError> {
+        Ok(BoundedPublicKey::from_p(
+            self.generate_public_key()?,
+            &self.p,
+        ))
     }
 
     /// Generates the private key
-    pub fn generate_private_key(&self) -> PrivateKey {
-        PrivateKey {
+    /// Returns an `InvalidKey` error if the cryptosystem was not initialized with `init`
+    pub fn generate_private_key(&self) -> Result<PrivateKey, OkamotoUchiyamaError> {
+        Ok(PrivateKey {
             // Private key contains the public key
-            public_key: self.generate_public_key().clone(),
+            public_key: self.generate_public_key()?,
 
             // Private key components
             gd: self.gpminuse1.clone(),
             p: self.p.clone(),
             q: self.q.clone(),
             p_squared: self.p_squared.clone(),
-        }
+            decryption_strategy: DecryptionStrategy::default(),
+        })
     }
 
     /// Encrypt a message using the public key.
+    /// Only messages below p decrypt to themselves, and the public key alone cannot check
+    /// it: use `BoundedPublicKey::encrypt` (see `generate_bounded_public_key`) to reject
+    /// oversized messages with a `MessageTooLarge` error.
     pub fn encrypt(message: &BigUint, public_key: &PublicKey) -> Ciphertext {
+        Self::encrypt_with_rng(message, public_key, &mut thread_rng())
+    }
+
+    /// Same as `encrypt`, drawing r from the given RNG, e.g. a seeded CSPRNG for
+    /// reproducible test vectors: the same seed yields the same ciphertext.
+    /// r is sampled from [1, n - 1) whatever the RNG.
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+        message: &BigUint,
+        public_key: &PublicKey,
+        rng: &mut R,
+    ) -> Ciphertext {
         // Choose a random integer r from {1...n-1}.
-        let mut rng = thread_rng();
         let n_minus_1 = &public_key.n - &BigUint::one();
         let r = rng.gen_biguint_range(&BigUint::one(), &n_minus_1);
 
         // Compute the ciphertext as c = (g^m * h^r) mod n.
-        let ciphertext_value = (public_key.g.modpow(&message, &public_key.n)
-            * public_key.h.modpow(&r, &public_key.n))
-            % &public_key.n;
+        public_key.encrypt_with_r(message, &r)
+    }
+
+    /// Encrypts a message, handling messages not below n as chosen by the policy rather
+    /// than implicitly, see `EncryptPolicy`.
+    /// Returns a `MessageTooLarge` error if the policy is `Strict` and the message is not
+    /// below n, or a `KeyMismatch` error if the private key of `ReduceModP` does not match
+    /// the public key.
+    pub fn encrypt_with_policy(
+        message: &BigUint,
+        public_key: &PublicKey,
+        policy: EncryptPolicy,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        match policy {
+            EncryptPolicy::Strict if message >= &public_key.n => {
+                Err(OkamotoUchiyamaError::MessageTooLarge)
+            }
+            EncryptPolicy::Strict => Ok(Self::encrypt(message, public_key)),
+            EncryptPolicy::ReduceModN => Ok(Self::encrypt(&(message % &public_key.n), public_key)),
+            EncryptPolicy::ReduceModP(private_key)
+                if !private_key.matches_public_key(public_key) =>
+            {
+                Err(OkamotoUchiyamaError::KeyMismatch)
+            }
+            EncryptPolicy::ReduceModP(private_key) => {
+                Ok(public_key.encrypt_reduced(message, private_key))
+            }
+        }
+    }
 
-        Ciphertext::new(ciphertext_value)
+    /// Encrypts a message with a randomness derived from a seed, for reproducible test vectors.
+    /// r is obtained by hashing `len(seed) || seed || message`, with the seed length as a
+    /// big-endian u64, with SHA-256 in counter mode and reducing the output into [1, n - 1],
+    /// so the same seed and message always give the same ciphertext. The length prefix keeps
+    /// distinct (seed, message) pairs with the same concatenation apart.
+    /// INSECURE: the ciphertext is deterministic and anyone knowing the seed can recover r,
+    /// never use this outside of tests.
+    pub fn encrypt_deterministic_from(
+        message: &BigUint,
+        public_key: &PublicKey,
+        seed: &[u8],
+    ) -> Ciphertext {
+        // Expand the hash to 128 bits more than n so the reduction is close to uniform
+        let n_len = public_key.n.bits().div_ceil(8) + 16;
+        let message_bytes = message.to_bytes_be();
+        let mut digest = Vec::with_capacity(n_len + 32);
+        let mut counter = 0u32;
+        while digest.len() < n_len {
+            let mut hasher = Sha256::new();
+            hasher.update(counter.to_be_bytes());
+            hasher.update((seed.len() as u64).to_be_bytes());
+            hasher.update(seed);
+            hasher.update(&message_bytes);
+            digest.extend_from_slice(&hasher.finalize());
+            counter += 1;
+        }
+
+        // Map the digest into [1, n - 1]
+        let n_minus_1 = &public_key.n - &BigUint::one();
+        let r = BigUint::from_bytes_be(&digest) % &n_minus_1 + BigUint::one();
+
+        // Compute the ciphertext as c = (g^m * h^r) mod n.
+        public_key.encrypt_with_r(message, &r)
     }
 
     /// Decrypts a ciphertext using the provided private key.
     pub fn decrypt(ciphertext: &Ciphertext, private_key: &PrivateKey) -> BigUint {
+        Self::decrypt_with_strategy(ciphertext, private_key, private_key.decryption_strategy())
+    }
+
+    /// Encrypts decimal integers read one per line and writes each ciphertext value in
+    /// decimal on its own line, in order, for unix pipelines; `decrypt_lines` reverses it.
+    /// Surrounding whitespace is ignored and blank lines are skipped without output.
+    /// Processing stops at the first line that is not a decimal integer with an
+    /// `InvalidLine` error holding its 1-based number; the lines before it have been written.
+    /// Returns the number of encrypted values.
+    pub fn encrypt_lines(
+        reader: impl BufRead,
+        public_key: &PublicKey,
+        mut writer: impl Write,
+    ) -> Result<usize, OkamotoUchiyamaError> {
+        let mut count = 0;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let message = BigUint::parse_bytes(line.as_bytes(), 10)
+                .ok_or(OkamotoUchiyamaError::InvalidLine(index + 1))?;
+            let ciphertext = Self::encrypt(&message, public_key);
+            writeln!(writer, "{}", ciphertext.value())?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Decrypts decimal ciphertext values read one per line, as written by `encrypt_lines`,
+    /// and writes each plaintext in decimal on its own line, in order.
+    /// Blank lines are skipped, and a line that is not a valid ciphertext for the key stops
+    /// processing with an `InvalidLine` error holding its 1-based number.
+    /// Returns the number of decrypted values.
+    pub fn decrypt_lines(
+        reader: impl BufRead,
+        private_key: &PrivateKey,
+        mut writer: impl Write,
+    ) -> Result<usize, OkamotoUchiyamaError> {
+        let mut count = 0;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let ciphertext = BigUint::parse_bytes(line.as_bytes(), 10)
+                .map(Ciphertext::new)
+                .filter(|c| private_key.public_key.check_ciphertext(c).is_ok())
+                .ok_or(OkamotoUchiyamaError::InvalidLine(index + 1))?;
+            writeln!(writer, "{}", Self::decrypt(&ciphertext, private_key))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    // Decrypts a ciphertext with the given decryption strategy
+    pub(crate) fn decrypt_with_strategy(
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+        strategy: DecryptionStrategy,
+    ) -> BigUint {
+        let a = Self::decryption_exponentiation(ciphertext, private_key, strategy);
+        Self::decrypt_from_exponentiation(&a, private_key)
+    }
+
+    // Computes a = c^(p-1) mod p^2, the first step of the decryption
+    pub(crate) fn decryption_exponentiation(
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+        strategy: DecryptionStrategy,
+    ) -> BigUint {
         let pminus1 = &private_key.p - 1u32;
 
         // c^(p-1) mod p^2
-        let a = ciphertext.value().modpow(&pminus1, &private_key.p_squared);
+        match strategy {
+            DecryptionStrategy::Standard => {
+                ciphertext.value().modpow(&pminus1, &private_key.p_squared)
+            }
+            // Reduce c mod p^2 first so the exponentiation works on smaller operands
+            DecryptionStrategy::ReduceFirst => (ciphertext.value() % &private_key.p_squared)
+                .modpow(&pminus1, &private_key.p_squared),
+        }
+    }
 
+    // Recovers the message from a = c^(p-1) mod p^2 as L(a) * L(gd)^(-1) mod p
+    pub(crate) fn decrypt_from_exponentiation(a: &BigUint, private_key: &PrivateKey) -> BigUint {
         // L1(a) = (a - 1) / p
         let l1 = (a - 1u32) / &private_key.p.clone();
 
@@ -153,3 +659,9 @@ impl OkamotoUchiyama {
         (l1 * binverse) % &private_key.p.clone()
     }
 }
+
+// Checks the primality of a BigUint with `MILLER_RABIN_ROUNDS` Miller-Rabin rounds, as run
+// by the prime generator. 0 and 1 are not prime.
+fn is_prime(n: &BigUint) -> bool {
+    probably_prime(n, MILLER_RABIN_ROUNDS as usize)
+}
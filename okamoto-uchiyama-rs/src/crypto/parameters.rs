@@ -0,0 +1,155 @@
+use crate::error::OkamotoUchiyamaError;
+use crate::pem::{
+    check_decoded_size, pem_decoding_error, to_asn1_bytes, PemEncodable, DEFAULT_MAX_PEM_BYTES,
+};
+
+use asn1::BigUint as Asn1BigUint;
+use asn1::ParseError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use num::ToPrimitive;
+use num_bigint_dig::BigUint;
+use std::fmt;
+
+/// The parameters chosen when generating an Okamoto-Uchiyama key pair.
+/// Unlike the private key, this captures the key generation inputs so that
+/// a deployment can be audited and the cryptosystem rebuilt with
+/// `OkamotoUchiyama::from_parameters`.
+/// The parameters are exported as a DER SEQUENCE {p, q, g, length} with `to_der`, or as
+/// a PEM block labeled `OKAMOTO-UCHIYAMA PARAMETERS` with `to_pem`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParameterSet {
+    // The prime p, the plaintext space is Z/pZ
+    pub p: BigUint,
+    // The prime q
+    pub q: BigUint,
+    // The generator g, in the range [2, n - 1] with g^(p-1) mod p^2 != 1
+    pub g: BigUint,
+    // The bit length of n = p^2 * q
+    pub length: u32,
+}
+
+impl ParameterSet {
+    /// Encodes the parameters as a DER SEQUENCE {p, q, g, length}
+    pub fn to_der(&self) -> Vec<u8> {
+        // Convert the parameters to ASN.1
+        let p_bytes = to_asn1_bytes(&self.p);
+        let p_asn1 = Asn1BigUint::new(&p_bytes);
+
+        let q_bytes = to_asn1_bytes(&self.q);
+        let q_asn1 = Asn1BigUint::new(&q_bytes);
+
+        let g_bytes = to_asn1_bytes(&self.g);
+        let g_asn1 = Asn1BigUint::new(&g_bytes);
+
+        let length_bytes = to_asn1_bytes(&BigUint::from(self.length));
+        let length_asn1 = Asn1BigUint::new(&length_bytes);
+
+        // Write all elements to ASN.1 Sequence
+        let result = asn1::write(|w| {
+            w.write_element(&asn1::SequenceWriter::new(&|w| {
+                w.write_element(&p_asn1)?;
+                w.write_element(&q_asn1)?;
+                w.write_element(&g_asn1)?;
+                w.write_element(&length_asn1)?;
+                Ok(())
+            }))
+        });
+
+        result.unwrap_or_else(|_| vec![])
+    }
+
+    /// Decodes the parameters from the DER bytes of a SEQUENCE {p, q, g, length}, as
+    /// produced by `to_der`, returning a `DecodingError` error if they are malformed or if
+    /// the length does not fit in a u32, or an `ElementCountMismatch` error if the SEQUENCE
+    /// does not hold exactly 4 INTEGERs.
+    /// The parameters are not validated, `OkamotoUchiyama::from_parameters` does it.
+    pub fn from_der(der: &[u8]) -> Result<Self, OkamotoUchiyamaError> {
+        // Parse the ASN.1 sequence into a list of BigUint elements
+        let elements = asn1::parse::<_, ParseError, _>(der, |d: &mut asn1::Parser<'_>| {
+            d.read_element::<asn1::Sequence>()?
+                .parse::<_, ParseError, _>(|d| {
+                    let mut elements = Vec::new();
+                    while !d.is_empty() {
+                        let element_asn1 = d.read_element::<Asn1BigUint>()?;
+                        elements.push(BigUint::from_bytes_be(element_asn1.as_bytes()));
+                    }
+                    Ok(elements)
+                })
+        })
+        .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+
+        match elements.as_slice() {
+            [p, q, g, length] => Ok(ParameterSet {
+                p: p.clone(),
+                q: q.clone(),
+                g: g.clone(),
+                length: length.to_u32().ok_or(OkamotoUchiyamaError::DecodingError)?,
+            }),
+            _ => Err(OkamotoUchiyamaError::ElementCountMismatch {
+                expected: 4,
+                found: elements.len(),
+            }),
+        }
+    }
+
+    /// Decodes PEM-encoded parameters produced by `to_pem`.
+    /// The decoded body is limited to `DEFAULT_MAX_PEM_BYTES` bytes
+    pub fn from_pem(pem: &str) -> Result<Self, OkamotoUchiyamaError> {
+        Self::from_pem_limited(pem, DEFAULT_MAX_PEM_BYTES)
+    }
+
+    /// Same as `from_pem`, but rejects inputs whose decoded body exceeds `max_bytes`
+    /// with an `InputTooLarge` error, before decoding them
+    pub fn from_pem_limited(pem: &str, max_bytes: usize) -> Result<Self, OkamotoUchiyamaError> {
+        // Trim the starting and ending spaces/newlines
+        let pem = pem.trim();
+
+        // Check if the PEM string starts and ends with the correct tags
+        if !pem.starts_with("-----BEGIN OKAMOTO-UCHIYAMA PARAMETERS-----")
+            || !pem.ends_with("-----END OKAMOTO-UCHIYAMA PARAMETERS-----")
+        {
+            return Err(OkamotoUchiyamaError::PemDecodingError(None));
+        }
+
+        // Extract the base64-encoded ASN.1 sequence between the tags
+        let base64_encoded = pem
+            .trim_start_matches("-----BEGIN OKAMOTO-UCHIYAMA PARAMETERS-----")
+            .trim_end_matches("-----END OKAMOTO-UCHIYAMA PARAMETERS-----")
+            .trim();
+
+        // Reject oversized inputs before decoding them
+        check_decoded_size(base64_encoded, max_bytes)?;
+
+        let asn1_decoded = STANDARD
+            .decode(base64_encoded)
+            .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+
+        Self::from_der(&asn1_decoded).map_err(pem_decoding_error)
+    }
+}
+
+// Implements Display trait for the ParameterSet struct
+impl fmt::Display for ParameterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ParameterSet {{\n  p: {},\n  q: {},\n  g: {},\n  length: {}\n}}",
+            self.p, self.q, self.g, self.length
+        )
+    }
+}
+
+/// Implements the PemEncodable trait for ParameterSet struct
+impl PemEncodable for ParameterSet {
+    fn to_pem(&self) -> String {
+        let mut pem = String::new();
+
+        // Encode the ASN.1 sequence using Base64
+        pem.push_str("-----BEGIN OKAMOTO-UCHIYAMA PARAMETERS-----\n");
+        pem.push_str(&STANDARD.encode(self.to_der()));
+        pem.push_str("\n-----END OKAMOTO-UCHIYAMA PARAMETERS-----\n");
+
+        pem
+    }
+}
@@ -0,0 +1,27 @@
+/// How the primes p and q of a cryptosystem were obtained
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrimeSource {
+    // The cryptosystem was not initialized
+    #[default]
+    Unknown,
+    // Freshly generated with the num_primes generator, by `init` or `init_with_ratio`
+    NumPrimes,
+    // Selected from caller-provided pools, by `init_from_prime_pool`
+    Pool,
+    // Provided by the caller, by `from_parameters`
+    Imported,
+}
+
+/// Metadata on how a cryptosystem was generated, for reproducibility audits.
+/// It records the generation path and tools, never secret material such as the primes,
+/// the RNG state or its seed, so it can be stored separately from the keys.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct KeyProvenance {
+    // Type name of the RNG that drew g, None if g was provided by the caller or derived
+    // from n by `init_with_derived_g`
+    pub rng: Option<&'static str>,
+    // How the primes were obtained
+    pub prime_source: PrimeSource,
+    // Number of Miller-Rabin rounds the primes passed
+    pub primality_rounds: u32,
+}
@@ -1,20 +1,43 @@
 use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::subtraction_context::SubtractionContext;
+use crate::crypto::tables::{EncryptionTables, FixedBaseTable};
 use crate::error::OkamotoUchiyamaError;
-use crate::pem::PemEncodable;
+use crate::pem::{
+    check_decoded_size, pem_decoding_error, read_detected, to_asn1_bytes, verify_checksum,
+    Detected, PemEncodable, DEFAULT_MAX_PEM_BYTES,
+};
 
 use asn1::BigUint as Asn1BigUint;
-use asn1::ParseError;
+use asn1::{BitString, ObjectIdentifier, ParseError};
 use base64::engine::general_purpose;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use num::One;
-use num_bigint_dig::BigUint;
+use num::{Integer, One, ToPrimitive, Zero};
+use num_bigint_dig::algorithms::mod_inverse;
+use num_bigint_dig::{BigUint, RandBigInt};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::cell::OnceCell;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::sync::{Arc, OnceLock};
 
 pub use crate::crypto::private_key::PrivateKey;
 
+/// Algorithm OID identifying Okamoto-Uchiyama public keys in a `SubjectPublicKeyInfo`.
+/// No OID is registered for the scheme, so this one lives under the `2.25` arc of
+/// ITU-T X.667, made of a random UUID, which needs no registration.
+pub const OKAMOTO_UCHIYAMA_OID: &str = "2.25.336443732362089740450781026313931844829";
+
+/// Suggested limit on the number of ciphertexts of an untrusted batch, see
+/// `PublicKey::homomorphic_encrypt_multiple_limited`. With a 2048-bit key, a batch of
+/// 2^16 ciphertexts amounts to 16 MiB of input.
+pub const DEFAULT_MAX_BATCH_TERMS: usize = 1 << 16;
+
 /// Represents an Okamoto-Uchiyama public key.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone)]
 pub struct PublicKey {
     // modulus: p^2 * q
     pub n: BigUint,
@@ -22,6 +45,28 @@ pub struct PublicKey {
     pub g: BigUint,
     // g^n mod n
     pub h: BigUint,
+    // Optional precomputed tables for encryption, excluded from equality and hashing
+    tables: Option<Arc<EncryptionTables>>,
+    // The n and g of the first call to `inv_g_mod_n` with g^(-1) mod n, None if g is not
+    // invertible, excluded from equality and hashing
+    inv_g: OnceLock<(BigUint, BigUint, Option<BigUint>)>,
+}
+
+// Two public keys are equal when their components are, whether or not they hold cached values
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && self.g == other.g && self.h == other.h
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl Hash for PublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.n.hash(state);
+        self.g.hash(state);
+        self.h.hash(state);
+    }
 }
 
 impl PublicKey {
@@ -31,11 +76,68 @@ impl PublicKey {
             n: n.clone(),
             g: g.clone(),
             h: h.clone(),
+            tables: None,
+            inv_g: OnceLock::new(),
         }
     }
 
+    /// Returns g^(-1) mod n, for operations that remove a power of g from a ciphertext.
+    /// It is computed on first use and cached along with the n and g it was computed for:
+    /// once either of them is modified, the cache is ignored and the inverse is computed on
+    /// each call.
+    /// Returns an `InvalidKey` error if g is not invertible mod n, which cannot happen for
+    /// a valid key.
+    pub fn inv_g_mod_n(&self) -> Result<BigUint, OkamotoUchiyamaError> {
+        let compute = || {
+            if self.n.is_zero() {
+                return None;
+            }
+            invert_mod(&self.g, &self.n).ok()
+        };
+
+        let (n, g, cached) = self
+            .inv_g
+            .get_or_init(|| (self.n.clone(), self.g.clone(), compute()));
+        let inverse = if (n, g) == (&self.n, &self.g) {
+            cached.clone()
+        } else {
+            compute()
+        };
+        inverse.ok_or(OkamotoUchiyamaError::InvalidKey)
+    }
+
     /// Decode a PEM-encoded public key string into a PublicKey instance
+    /// The decoded body is limited to `DEFAULT_MAX_PEM_BYTES` bytes
     pub fn from_pem(pem: &str) -> Result<Self, OkamotoUchiyamaError> {
+        Self::from_pem_limited(pem, DEFAULT_MAX_PEM_BYTES)
+    }
+
+    /// Reduces g and h mod n into their canonical range.
+    /// Returns an `InvalidKey` error if n is zero, or if g reduces to 0 or 1, or h to 0,
+    /// as such keys would produce ciphertexts that do not hide or carry the message.
+    pub fn normalized(self) -> Result<Self, OkamotoUchiyamaError> {
+        if self.n.is_zero() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        let g = &self.g % &self.n;
+        let h = &self.h % &self.n;
+        if g <= BigUint::one() || h.is_zero() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        Ok(PublicKey::new(&self.n, &g, &h))
+    }
+
+    /// Same as `from_pem`, but expects a PEM string produced by `to_pem_checked` and
+    /// returns a `ChecksumMismatch` error if its content does not match the checksum
+    pub fn from_pem_checked(pem: &str) -> Result<Self, OkamotoUchiyamaError> {
+        Self::from_pem(&verify_checksum(pem)?)
+    }
+
+    /// Same as `from_pem`, but rejects inputs whose decoded body exceeds `max_bytes`
+    /// with an `InputTooLarge` error, before decoding them
+    pub fn from_pem_limited(pem: &str, max_bytes: usize) -> Result<Self, OkamotoUchiyamaError> {
         // Trim the starting and ending spaces/newlines
         let pem = pem.trim();
 
@@ -43,7 +145,7 @@ impl PublicKey {
         if !pem.starts_with("-----BEGIN PUBLIC KEY-----")
             || !pem.ends_with("-----END PUBLIC KEY-----")
         {
-            return Err(OkamotoUchiyamaError::PemDecodingError);
+            return Err(OkamotoUchiyamaError::PemDecodingError(None));
         }
 
         // Extract the base64-encoded ASN.1 sequence between the tags
@@ -52,38 +154,491 @@ impl PublicKey {
             .trim_end_matches("-----END PUBLIC KEY-----")
             .trim();
 
+        // Reject oversized inputs before decoding them
+        check_decoded_size(base64_encoded, max_bytes)?;
+
         // Decode the base64-encoded ASN.1 sequence using Engine::decode
         let asn1_decoded = STANDARD
             .decode(base64_encoded)
-            .map_err(|_| OkamotoUchiyamaError::PemDecodingError)?;
-
-        // Parse the ASN.1 sequence into the PublicKey struct
-        let (n, g, h) =
-            asn1::parse::<_, ParseError, _>(&asn1_decoded, |d: &mut asn1::Parser<'_>| {
-                d.read_element::<asn1::Sequence>()?
-                    .parse::<_, ParseError, _>(|d| {
-                        // Parse ASN.1 BigUint elements
-                        let n_asn1 = d.read_element::<Asn1BigUint>()?;
-                        let g_asn1 = d.read_element::<Asn1BigUint>()?;
-                        let h_asn1 = d.read_element::<Asn1BigUint>()?;
-
-                        // Convert ASN.1 BigUint to BigUint
-                        let n_bytes = n_asn1.as_bytes();
-                        let g_bytes = g_asn1.as_bytes();
-                        let h_bytes = h_asn1.as_bytes();
-
-                        // Convert bytes back to BigUint
-                        let n = BigUint::from_bytes_be(&n_bytes);
-                        let g = BigUint::from_bytes_be(&g_bytes);
-                        let h = BigUint::from_bytes_be(&h_bytes);
-
-                        Ok((n, g, h))
-                    })
-            })
-            .map_err(|_| OkamotoUchiyamaError::PemDecodingError)?;
+            .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+
+        Self::from_der(&asn1_decoded).map_err(pem_decoding_error)
+    }
+
+    /// Loads a public key from a reader, detecting whether it holds a PEM string (checked
+    /// or not) or raw DER bytes.
+    /// PEM is recognized by its `-----BEGIN ` armor, any other input is parsed as DER and
+    /// must start with the SEQUENCE tag 0x30. There is no other binary encoding of keys,
+    /// and the armor cannot be mistaken for DER, so the detection is never ambiguous.
+    pub fn load(reader: impl Read) -> Result<Self, OkamotoUchiyamaError> {
+        match read_detected(reader)? {
+            Detected::Pem(pem) => Self::from_pem(&pem),
+            Detected::CheckedPem(pem) => Self::from_pem_checked(&pem),
+            Detected::Der(der) => Self::from_der(&der),
+        }
+    }
+
+    /// Decodes a public key from the DER bytes of a SEQUENCE {n, g, h}, as produced by
+    /// `to_der`, returning a `DecodingError` error if they are malformed, or an
+    /// `ElementCountMismatch` error if the SEQUENCE does not hold exactly 3 INTEGERs
+    pub fn from_der(der: &[u8]) -> Result<Self, OkamotoUchiyamaError> {
+        // Parse the ASN.1 sequence into a list of BigUint elements
+        let elements = asn1::parse::<_, ParseError, _>(der, |d: &mut asn1::Parser<'_>| {
+            d.read_element::<asn1::Sequence>()?
+                .parse::<_, ParseError, _>(|d| {
+                    let mut elements = Vec::new();
+                    while !d.is_empty() {
+                        // Parse ASN.1 BigUint element and convert it back to BigUint
+                        let element_asn1 = d.read_element::<Asn1BigUint>()?;
+                        elements.push(BigUint::from_bytes_be(element_asn1.as_bytes()));
+                    }
+                    Ok(elements)
+                })
+        })
+        .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
 
-        // Create and return PublicKey instance
-        Ok(PublicKey::new(&n, &g, &h))
+        // Create and return the normalized PublicKey instance
+        match elements.as_slice() {
+            [n, g, h] => PublicKey::new(n, g, h).normalized(),
+            _ => Err(OkamotoUchiyamaError::ElementCountMismatch {
+                expected: 3,
+                found: elements.len(),
+            }),
+        }
+    }
+
+    /// Encodes the public key as a DER `SubjectPublicKeyInfo`, as embedded in X.509
+    /// certificates:
+    /// SEQUENCE { SEQUENCE { OBJECT IDENTIFIER }, BIT STRING }
+    /// where the OID is `OKAMOTO_UCHIYAMA_OID` with absent parameters, and the BIT STRING,
+    /// without unused bits, holds the DER SEQUENCE {n, g, h} produced by `to_der`.
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        let key_der = self.to_der();
+        let oid = ObjectIdentifier::from_string(OKAMOTO_UCHIYAMA_OID).unwrap();
+
+        let result = asn1::write(|w| {
+            w.write_element(&asn1::SequenceWriter::new(&|w| {
+                // AlgorithmIdentifier
+                w.write_element(&asn1::SequenceWriter::new(&|w| w.write_element(&oid)))?;
+                // subjectPublicKey
+                w.write_element(&BitString::new(&key_der, 0).unwrap())?;
+                Ok(())
+            }))
+        });
+
+        result.unwrap_or_else(|_| vec![])
+    }
+
+    /// Decodes a public key from a DER `SubjectPublicKeyInfo` produced by `to_spki_der`.
+    /// Returns a `DecodingError` error if the structure is malformed, or if its algorithm is
+    /// not `OKAMOTO_UCHIYAMA_OID`.
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, OkamotoUchiyamaError> {
+        let expected_oid = ObjectIdentifier::from_string(OKAMOTO_UCHIYAMA_OID).unwrap();
+
+        let key_der = asn1::parse::<_, ParseError, _>(der, |d: &mut asn1::Parser<'_>| {
+            d.read_element::<asn1::Sequence>()?
+                .parse::<_, ParseError, _>(|d| {
+                    let oid = d
+                        .read_element::<asn1::Sequence>()?
+                        .parse::<_, ParseError, _>(|d| d.read_element::<ObjectIdentifier>())?;
+                    let key_bits = d.read_element::<BitString>()?;
+                    Ok((oid, key_bits.padding_bits(), key_bits.as_bytes().to_vec()))
+                })
+        })
+        .map_err(|_| OkamotoUchiyamaError::DecodingError)
+        .and_then(|(oid, padding_bits, key_der)| {
+            if oid != expected_oid || padding_bits != 0 {
+                return Err(OkamotoUchiyamaError::DecodingError);
+            }
+            Ok(key_der)
+        })?;
+
+        Self::from_der(&key_der)
+    }
+
+    /// Encodes the public key as a DER SEQUENCE {n, g, h}
+    pub fn to_der(&self) -> Vec<u8> {
+        // Convert public key components to ASN.1
+        let n_bytes = to_asn1_bytes(&self.n);
+        let n_asn1 = Asn1BigUint::new(&n_bytes);
+
+        let g_bytes = to_asn1_bytes(&self.g);
+        let g_asn1 = Asn1BigUint::new(&g_bytes);
+
+        let h_bytes = to_asn1_bytes(&self.h);
+        let h_asn1 = Asn1BigUint::new(&h_bytes);
+
+        // Write all elements to ASN.1 Sequence
+        let result = asn1::write(|w| {
+            w.write_element(&asn1::SequenceWriter::new(&|w| {
+                w.write_element(&n_asn1)?; // Add n to the sequence
+                w.write_element(&g_asn1)?; // Add g to the sequence
+                w.write_element(&h_asn1)?; // Add h to the sequence
+                Ok(())
+            }))
+        });
+
+        result.unwrap_or_else(|_| vec![])
+    }
+
+    // Tags the result of a homomorphic operation when one of its operands is tagged.
+    // The operands have already been checked against this key, so their tag is the
+    // fingerprint of the key and is reused instead of being computed again.
+    pub(crate) fn homomorphic_result(
+        &self,
+        value: BigUint,
+        operands: &[&Ciphertext],
+    ) -> Ciphertext {
+        match operands.iter().find_map(|c| c.key_id()) {
+            Some(key_id) => Ciphertext::tagged(value, *key_id),
+            None => Ciphertext::new(value),
+        }
+    }
+
+    /// Precomputes fixed-base exponentiation tables of g and h with windows of
+    /// `window_bits` bits (clamped to [1, 8]), used by every subsequent encryption.
+    /// Each exponentiation then costs one multiplication per window instead of one squaring
+    /// per bit, at the price of storing 2^window_bits powers per window for each base.
+    /// Windows below 4 bits are usually slower than the plain exponentiation, 6 bits is a good
+    /// trade-off. Exponents larger than n fall back to the plain exponentiation.
+    /// The tables remember the n, g and h they were built for: once any of them is modified,
+    /// they are ignored until this is called again. See `benches/encryption_tables.rs`.
+    pub fn precompute_encryption_tables(&mut self, window_bits: u32) {
+        let window_bits = window_bits.clamp(1, 8);
+        let max_exponent_bits = self.n.bits();
+
+        self.tables = Some(Arc::new(EncryptionTables {
+            key: (self.n.clone(), self.g.clone(), self.h.clone()),
+            g: FixedBaseTable::new(&self.g, &self.n, window_bits, max_exponent_bits),
+            h: FixedBaseTable::new(&self.h, &self.n, window_bits, max_exponent_bits),
+        }));
+    }
+
+    /// Returns the precomputed encryption tables, if any were built for the current
+    /// n, g and h
+    pub fn encryption_tables(&self) -> Option<&EncryptionTables> {
+        self.tables
+            .as_deref()
+            .filter(|tables| tables.built_for(&self.n, &self.g, &self.h))
+    }
+
+    // Computes h^r mod n, using the precomputed table when available
+    fn pow_h(&self, r: &BigUint) -> BigUint {
+        self.encryption_tables()
+            .and_then(|tables| tables.h.pow(r, &self.n))
+            .unwrap_or_else(|| self.h.modpow(r, &self.n))
+    }
+
+    // Computes g^m mod n. Messages below 256, as in counters and votes, use a plain
+    // square-and-multiply, which skips the setup cost of the general modpow; beyond that
+    // the general modpow is faster. With a 1024-bit key (benches/small_messages.rs), the
+    // square-and-multiply is about 2x faster for 8-bit messages but 0.85x as fast for
+    // 16-bit ones and 0.45x for 32-bit ones, so the fast path is kept to u8.
+    fn pow_g(&self, message: &BigUint) -> BigUint {
+        if let Some(result) = self
+            .encryption_tables()
+            .and_then(|tables| tables.g.pow(message, &self.n))
+        {
+            return result;
+        }
+
+        let Some(small) = message.to_u8() else {
+            return self.g.modpow(message, &self.n);
+        };
+
+        let mut result = BigUint::one() % &self.n;
+        for i in (0..u8::BITS - small.leading_zeros()).rev() {
+            result = (&result * &result) % &self.n;
+            if (small >> i) & 1 == 1 {
+                result = (&result * &self.g) % &self.n;
+            }
+        }
+        result
+    }
+
+    // Computes the ciphertext c = (g^m * h^r) mod n for a given randomness r
+    pub(crate) fn encrypt_with_r(&self, message: &BigUint, r: &BigUint) -> Ciphertext {
+        let ciphertext_value = (self.pow_g(message) * self.pow_h(r)) % &self.n;
+        Ciphertext::new(ciphertext_value)
+    }
+
+    /// Encrypts a message into a reused `BigUint`, drawing r from the given rng, for
+    /// encryption loops that keep the ciphertext values rather than `Ciphertext`s.
+    /// The exponentiations still allocate their intermediates, so the saving over `encrypt`
+    /// is limited to the output, and is within noise with a 1024-bit key
+    /// (see benches/encrypt_into.rs).
+    pub fn encrypt_into<R: RngCore + CryptoRng>(
+        &self,
+        message: &BigUint,
+        out: &mut BigUint,
+        rng: &mut R,
+    ) {
+        let r = rng.gen_biguint_range(&BigUint::one(), &(&self.n - 1u32));
+        *out = self.pow_g(message) * self.pow_h(&r);
+        *out %= &self.n;
+    }
+
+    /// Encrypts a plaintext sampled uniformly from [0, bound) with the given rng, e.g. to
+    /// mask a value, and returns the ciphertext along with the plaintext so the mask can be
+    /// removed later. The caller must keep the plaintext secret, as it unmasks the value.
+    /// Panics if `bound` is zero.
+    pub fn encrypt_random<R: RngCore + CryptoRng>(
+        &self,
+        bound: &BigUint,
+        rng: &mut R,
+    ) -> (Ciphertext, BigUint) {
+        let mask = rng.gen_biguint_below(bound);
+        let r = rng.gen_biguint_range(&BigUint::one(), &(&self.n - 1u32));
+        (self.encrypt_with_r(&mask, &r), mask)
+    }
+
+    /// Encrypts a message and writes the big-endian bytes of the ciphertext value into a
+    /// reused buffer, replacing its content but keeping its capacity
+    pub fn encrypt_bytes_into(&self, message: &BigUint, out: &mut Vec<u8>) {
+        let ciphertext = OkamotoUchiyama::encrypt(message, self);
+        out.clear();
+        out.extend_from_slice(&ciphertext.value().to_bytes_be());
+    }
+
+    /// Returns n as little-endian bytes, e.g. for FFI consumers rebuilding the integer
+    /// without parsing PEM. The bytes are minimal: the last byte is non-zero, except for a
+    /// zero value, which is a single zero byte.
+    pub fn n_le_bytes(&self) -> Vec<u8> {
+        self.n.to_bytes_le()
+    }
+
+    /// Returns g as little-endian bytes, with the same layout as `n_le_bytes`
+    pub fn g_le_bytes(&self) -> Vec<u8> {
+        self.g.to_bytes_le()
+    }
+
+    /// Returns h as little-endian bytes, with the same layout as `n_le_bytes`
+    pub fn h_le_bytes(&self) -> Vec<u8> {
+        self.h.to_bytes_le()
+    }
+
+    /// Returns the byte length of n, which is the length of the big-endian encoding of the
+    /// largest ciphertext values under this key
+    pub fn ciphertext_byte_len(&self) -> usize {
+        self.n.bits().div_ceil(8)
+    }
+
+    /// Computes base^exp mod n, for protocols built on top of the key
+    pub fn pow_mod_n(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        base.modpow(exp, &self.n)
+    }
+
+    /// Returns the fingerprint of the public key, the SHA-256 digest of its DER encoding
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.to_der()).into()
+    }
+
+    /// Estimates the security level of the key in bits, from the heuristic cost of
+    /// factoring n with the general number field sieve,
+    /// exp(1.923 * ln(n)^(1/3) * ln(ln(n))^(2/3)), rounded down. It gives 86 bits for a
+    /// 1024-bit n and 116 bits for a 2048-bit n, slightly above the usual estimates of 80 and
+    /// 112 bits, as it ignores the constant factors of the sieve.
+    pub fn estimated_security_bits(&self) -> u32 {
+        let ln_n = self.n.bits() as f64 * std::f64::consts::LN_2;
+        if ln_n <= 1.0 {
+            return 0;
+        }
+        let cost = 1.923 * ln_n.cbrt() * ln_n.ln().powf(2.0 / 3.0);
+        (cost / std::f64::consts::LN_2) as u32
+    }
+
+    /// Encodes the public key as a key directory entry, the record a key server publishes:
+    /// a JSON object holding the base64 DER `SubjectPublicKeyInfo` (`spki`), the hex
+    /// fingerprint (`fingerprint`), the bit length of n (`modulus_bits`) and the estimated
+    /// security level (`security_bits`), e.g.
+    /// `{"spki":"MIIB...","fingerprint":"9f86...","modulus_bits":1024,"security_bits":86}`
+    pub fn to_directory_entry(&self) -> String {
+        let fingerprint: String = self
+            .fingerprint()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!(
+            "{{\"spki\":\"{}\",\"fingerprint\":\"{}\",\"modulus_bits\":{},\"security_bits\":{}}}",
+            STANDARD.encode(self.to_spki_der()),
+            fingerprint,
+            self.n.bits(),
+            self.estimated_security_bits()
+        )
+    }
+
+    /// Decodes a key directory entry produced by `to_directory_entry` and verifies that its
+    /// fingerprint and modulus bit length match the decoded key, returning a
+    /// `FingerprintMismatch` error otherwise. The security level is informative and not
+    /// checked. Only flat objects with string and integer values are accepted, in any order
+    /// and with any whitespace between tokens; a malformed entry is a `DecodingError`.
+    pub fn from_directory_entry(entry: &str) -> Result<Self, OkamotoUchiyamaError> {
+        let fields = parse_flat_json(entry).ok_or(OkamotoUchiyamaError::DecodingError)?;
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+                .ok_or(OkamotoUchiyamaError::DecodingError)
+        };
+
+        let spki = STANDARD
+            .decode(field("spki")?)
+            .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+        let public_key = Self::from_spki_der(&spki)?;
+
+        let fingerprint: String = public_key
+            .fingerprint()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let modulus_bits: u64 = field("modulus_bits")?
+            .parse()
+            .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+        if !field("fingerprint")?.eq_ignore_ascii_case(&fingerprint)
+            || modulus_bits != public_key.n.bits() as u64
+        {
+            return Err(OkamotoUchiyamaError::FingerprintMismatch);
+        }
+        Ok(public_key)
+    }
+
+    /// Returns the names of the components (`"n"`, `"g"`, `"h"`) that differ between two
+    /// public keys, for debugging. Cached values such as tables are not compared.
+    pub fn diff(&self, other: &PublicKey) -> Vec<&'static str> {
+        [
+            ("n", self.n == other.n),
+            ("g", self.g == other.g),
+            ("h", self.h == other.h),
+        ]
+        .into_iter()
+        .filter(|(_, equal)| !equal)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /// Encrypts a message and tags the ciphertext with the fingerprint of this key.
+    /// Homomorphic operations reject tagged ciphertexts coming from another key with a
+    /// `KeyMismatch` error, while untagged ciphertexts are accepted as before.
+    pub fn encrypt_tagged(&self, message: &BigUint) -> Ciphertext {
+        let ciphertext = OkamotoUchiyama::encrypt(message, self);
+        Ciphertext::tagged(ciphertext.value().clone(), self.fingerprint())
+    }
+
+    /// Encrypts a public constant without randomness, i.e. computes g^k mod n (r = 0).
+    /// The result is deterministic and therefore not semantically secure: only use it for
+    /// constants that are public anyway, e.g. to add k to a ciphertext by multiplying them.
+    pub fn encrypt_constant(&self, k: &BigUint) -> Ciphertext {
+        self.encrypt_with_r(k, &BigUint::zero())
+    }
+
+    /// Encrypts m mod p instead of m, so the ciphertext always decrypts to the reduced value.
+    /// This changes the plaintext of messages not below p, which may hide a bug on the caller
+    /// side; use `BoundedPublicKey::encrypt` to get a `MessageTooLarge` error instead.
+    /// Reducing needs p, hence the private key, which must match this public key.
+    pub fn encrypt_reduced(&self, m: &BigUint, private_key: &PrivateKey) -> Ciphertext {
+        OkamotoUchiyama::encrypt(&(m % &private_key.p), self)
+    }
+
+    /// Encrypts every value of a column, preserving their order
+    pub fn encrypt_column<I: IntoIterator<Item = BigUint>>(&self, col: I) -> Vec<Ciphertext> {
+        col.into_iter()
+            .map(|message| OkamotoUchiyama::encrypt(&message, self))
+            .collect()
+    }
+
+    /// Encrypts a record, i.e. the fields of a row of a fixed-schema table, preserving their
+    /// order. To aggregate a column across records, gather the ciphertexts of that field
+    /// from each record and pass them to `homomorphic_sum_columns`.
+    pub fn encrypt_record(&self, fields: &[BigUint]) -> Vec<Ciphertext> {
+        self.encrypt_column(fields.iter().cloned())
+    }
+
+    /// Encrypts a message with a randomness bound to an application-specific context.
+    /// The randomness is r = (r' + SHA-256(context)) mod n, where r' is drawn uniformly
+    /// from [0, n) with the given rng, so r stays uniform and secret while the same rng
+    /// output yields different ciphertexts in different contexts.
+    /// This only separates contexts, it does not authenticate the ciphertext nor the context.
+    pub fn encrypt_with_context<R: RngCore + CryptoRng>(
+        &self,
+        message: &BigUint,
+        context: &[u8],
+        rng: &mut R,
+    ) -> Ciphertext {
+        let context_hash = BigUint::from_bytes_be(&Sha256::digest(context));
+
+        loop {
+            let r = (rng.gen_biguint_below(&self.n) + &context_hash) % &self.n;
+            // r = 0 would leave the message unmasked
+            if !r.is_zero() {
+                return self.encrypt_with_r(message, &r);
+            }
+        }
+    }
+
+    /// Checks whether a ciphertext can be valid under this public key:
+    /// it must lie in the range [1, n - 1] and be coprime with n.
+    /// This is a necessary but not sufficient condition, it does not prove that
+    /// the ciphertext was produced with this key.
+    pub fn accepts(&self, c: &Ciphertext) -> bool {
+        self.check_ciphertext(c).is_ok()
+    }
+
+    /// Validates every ciphertext of a slice as `accepts` does, before bulk processing.
+    /// Returns an `InvalidCiphertextAt` error holding the index of the first ciphertext that
+    /// is rejected. The fingerprint of the key is computed once for the whole slice.
+    pub fn validate_ciphertexts(&self, cts: &[Ciphertext]) -> Result<(), OkamotoUchiyamaError> {
+        let fingerprint = OnceCell::new();
+
+        for (index, c) in cts.iter().enumerate() {
+            let valid = c
+                .key_id()
+                .is_none_or(|key_id| key_id == fingerprint.get_or_init(|| self.fingerprint()))
+                && c.value() < &self.n
+                && !c.value().is_zero()
+                && c.value().gcd(&self.n).is_one();
+            if !valid {
+                return Err(OkamotoUchiyamaError::InvalidCiphertextAt(index));
+            }
+        }
+        Ok(())
+    }
+
+    // Validates a ciphertext before using it in a homomorphic operation
+    pub(crate) fn check_ciphertext(&self, c: &Ciphertext) -> Result<(), OkamotoUchiyamaError> {
+        self.check_ciphertext_with(c, &OnceCell::new())
+    }
+
+    // Validates ciphertexts as `check_ciphertext` does, computing the fingerprint of the key
+    // at most once for all of them
+    pub(crate) fn check_ciphertexts<'c>(
+        &self,
+        cts: impl IntoIterator<Item = &'c Ciphertext>,
+    ) -> Result<(), OkamotoUchiyamaError> {
+        let fingerprint = OnceCell::new();
+        cts.into_iter()
+            .try_for_each(|c| self.check_ciphertext_with(c, &fingerprint))
+    }
+
+    // Validates a ciphertext, the fingerprint of the key being computed on the first
+    // tagged ciphertext and kept in the cell for the next ones
+    fn check_ciphertext_with(
+        &self,
+        c: &Ciphertext,
+        fingerprint: &OnceCell<[u8; 32]>,
+    ) -> Result<(), OkamotoUchiyamaError> {
+        if let Some(key_id) = c.key_id() {
+            if key_id != fingerprint.get_or_init(|| self.fingerprint()) {
+                return Err(OkamotoUchiyamaError::KeyMismatch);
+            }
+        }
+        if c.value() >= &self.n {
+            return Err(OkamotoUchiyamaError::CipherTooLarge);
+        }
+        if c.value().is_zero() || !c.value().gcd(&self.n).is_one() {
+            return Err(OkamotoUchiyamaError::InvalidCiphertext);
+        }
+        Ok(())
     }
 
     /// Performs homomorphic operation over two passed ciphertexts.
@@ -94,13 +649,11 @@ impl PublicKey {
         c1: &Ciphertext,
         c2: &Ciphertext,
     ) -> Result<Ciphertext, OkamotoUchiyamaError> {
-        if c1.value() == &self.n || c2.value() == &self.n {
-            return Err(OkamotoUchiyamaError::CipherTooLarge);
-        }
+        self.check_ciphertexts([c1, c2])?;
 
         // Calculate the product of the two ciphertexts and take the modulus by the public key n.
         let result_value = (c1.value() * c2.value()) % &self.n;
-        Ok(Ciphertext::new(result_value))
+        Ok(self.homomorphic_result(result_value, &[c1, c2]))
     }
 
     /// Performs homomorphic operation over multiple passed ciphertexts.
@@ -110,19 +663,467 @@ impl PublicKey {
         &self,
         ciphers: Vec<&Ciphertext>,
     ) -> Result<Ciphertext, OkamotoUchiyamaError> {
-        // Check if any ciphertext in the vector has the same value as the public key n.
-        if ciphers.iter().any(|&cipher| cipher.value() == &self.n) {
-            return Err(OkamotoUchiyamaError::CipherTooLarge);
-        }
+        // Check that every ciphertext in the vector is valid under the public key.
+        self.check_ciphertexts(ciphers.iter().copied())?;
 
-        // Calculate the product of all ciphertexts in the vector and return it.
+        // Calculate the product of all ciphertexts in the vector, reducing it mod n at every
+        // step so that it stays as large as n.
         let mut result = BigUint::one();
-        for cipher in ciphers {
-            result = &result * cipher.value();
+        for cipher in &ciphers {
+            result = (&result * cipher.value()) % &self.n;
+        }
+        Ok(self.homomorphic_result(result, &ciphers))
+    }
+
+    /// Same as `homomorphic_encrypt_multiple`, but returns a `BatchTooLarge` error without
+    /// processing anything if there are more than `max_terms` ciphertexts, to bound the time
+    /// spent on untrusted batches. Memory is bounded already, since the product is reduced
+    /// mod n at every step. `DEFAULT_MAX_BATCH_TERMS` is a reasonable limit.
+    pub fn homomorphic_encrypt_multiple_limited(
+        &self,
+        ciphers: Vec<&Ciphertext>,
+        max_terms: usize,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        if ciphers.len() > max_terms {
+            return Err(OkamotoUchiyamaError::BatchTooLarge);
+        }
+        self.homomorphic_encrypt_multiple(ciphers)
+    }
+
+    /// Computes the encrypted sum of the plaintexts of a slice of ciphertexts.
+    /// Same as `homomorphic_encrypt_multiple`, spelled out for aggregation code.
+    pub fn homomorphic_sum_of(
+        &self,
+        cts: &[Ciphertext],
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.homomorphic_encrypt_multiple(cts.iter().collect())
+    }
+
+    /// Computes the encrypted Hamming weight of a bit vector encrypted bit by bit, i.e. the
+    /// encrypted count of ones, as the homomorphic sum of the bit ciphertexts.
+    /// Whether the plaintexts are bits cannot be checked here; decrypting the result with
+    /// `PrivateKey::decrypt_count` catches counts that only non-bit inputs can produce.
+    pub fn encrypted_hamming_weight(
+        &self,
+        bits: &[Ciphertext],
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.homomorphic_encrypt_multiple(bits.iter().collect())
+    }
+
+    /// Computes the encrypted sum of every window of `window` consecutive ciphertexts, in order,
+    /// e.g. for moving sums over an encrypted time series.
+    /// Each window is obtained from the previous one by dividing out the exiting ciphertext
+    /// (multiplying by its inverse mod n) and multiplying in the entering one, so the cost does
+    /// not depend on the window size. Every ciphertext is validated first, which guarantees the
+    /// inverses exist. Returns an `InvalidWindow` error if `window` is 0 or larger than `cts`.
+    pub fn sliding_window_sum(
+        &self,
+        cts: &[Ciphertext],
+        window: usize,
+    ) -> Result<Vec<Ciphertext>, OkamotoUchiyamaError> {
+        if window == 0 || window > cts.len() {
+            return Err(OkamotoUchiyamaError::InvalidWindow);
+        }
+        self.validate_ciphertexts(cts)?;
+
+        // The sums are tagged if any of the inputs is
+        let tagged: Vec<&Ciphertext> = cts
+            .iter()
+            .find(|c| c.key_id().is_some())
+            .into_iter()
+            .collect();
+
+        // Product of the first window
+        let mut product = BigUint::one();
+        for c in &cts[..window] {
+            product = (&product * c.value()) % &self.n;
+        }
+
+        let mut sums = Vec::with_capacity(cts.len() - window + 1);
+        sums.push(self.homomorphic_result(product.clone(), &tagged));
+        for (exiting, entering) in cts.iter().zip(&cts[window..]) {
+            let inverse = invert_mod(exiting.value(), &self.n)?;
+            product = (&product * inverse) % &self.n;
+            product = (&product * entering.value()) % &self.n;
+            sums.push(self.homomorphic_result(product.clone(), &tagged));
+        }
+        Ok(sums)
+    }
+
+    /// Computes one encrypted sum per column of ciphertexts.
+    /// This is the multi-column generalization of `homomorphic_encrypt_multiple`: all the
+    /// columns must have the same length, otherwise a `RaggedColumns` error is returned,
+    /// and every ciphertext is validated before any aggregation.
+    pub fn homomorphic_sum_columns(
+        &self,
+        columns: &[Vec<Ciphertext>],
+    ) -> Result<Vec<Ciphertext>, OkamotoUchiyamaError> {
+        // Check that all the columns have the same length
+        if let Some(first) = columns.first() {
+            if columns.iter().any(|column| column.len() != first.len()) {
+                return Err(OkamotoUchiyamaError::RaggedColumns);
+            }
+        }
+
+        // Check that every ciphertext is valid under the public key.
+        self.check_ciphertexts(columns.iter().flatten())?;
+
+        // Calculate the product of each column and take the modulus by the public key n.
+        Ok(columns
+            .iter()
+            .map(|column| {
+                let mut result = BigUint::one();
+                for cipher in column {
+                    result = (&result * cipher.value()) % &self.n;
+                }
+                self.homomorphic_result(result, &column.iter().collect::<Vec<_>>())
+            })
+            .collect())
+    }
+
+    /// Aggregates client submissions for a variance computation.
+    /// Squaring is not supported homomorphically, so each client submits the pair
+    /// (Enc(x_i), Enc(x_i^2)) and the server returns (Enc(sum x_i), Enc(sum x_i^2)).
+    /// The private key holder then decrypts both aggregates and computes the variance as
+    /// sum(x_i^2) / k - (sum(x_i) / k)^2. Both sums must stay below p.
+    pub fn variance_prep(
+        &self,
+        submissions: &[(Ciphertext, Ciphertext)],
+    ) -> Result<(Ciphertext, Ciphertext), OkamotoUchiyamaError> {
+        let values: Vec<&Ciphertext> = submissions.iter().map(|(x, _)| x).collect();
+        let squares: Vec<&Ciphertext> = submissions.iter().map(|(_, x2)| x2).collect();
+
+        Ok((
+            self.homomorphic_encrypt_multiple(values)?,
+            self.homomorphic_encrypt_multiple(squares)?,
+        ))
+    }
+
+    // Multiplies the plaintext of a ciphertext by a public constant, i.e. computes c^k mod n
+    pub(crate) fn multiply_constant(
+        &self,
+        c: &Ciphertext,
+        k: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertext(c)?;
+        Ok(self.homomorphic_result(c.value().modpow(k, &self.n), &[c]))
+    }
+
+    /// Applies the affine transform k * m + j to the plaintext of a ciphertext,
+    /// i.e. computes c^k * g^j mod n in a single call.
+    /// The result decrypts to (k * m + j) mod p, so it wraps around when k * m + j >= p.
+    pub fn homomorphic_affine(
+        &self,
+        c: &Ciphertext,
+        k: &BigUint,
+        j: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertext(c)?;
+
+        let result_value = (c.value().modpow(k, &self.n) * self.pow_g(j)) % &self.n;
+        Ok(self.homomorphic_result(result_value, &[c]))
+    }
+
+    /// Evaluates the polynomial a_0 + a_1 * x + ... + a_k * x^k with public coefficients
+    /// on an encrypted x, i.e. computes g^a_0 * enc(x)^a_1 * ... * enc(x^k)^a_k mod n.
+    /// Powers of x cannot be computed homomorphically, so the client holding x submits the
+    /// encryptions `power_cts = [enc(x), enc(x^2), ..., enc(x^k)]`; `coeffs` holds the
+    /// constant term followed by one coefficient per power. The result is reduced mod p.
+    /// Returns a `LengthMismatch` error unless `coeffs` has one more element than `power_cts`.
+    pub fn homomorphic_poly_eval(
+        &self,
+        power_cts: &[Ciphertext],
+        coeffs: &[BigUint],
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        let Some((constant, coeffs)) = coeffs.split_first() else {
+            return Err(OkamotoUchiyamaError::LengthMismatch);
+        };
+        if coeffs.len() != power_cts.len() {
+            return Err(OkamotoUchiyamaError::LengthMismatch);
+        }
+        self.check_ciphertexts(power_cts)?;
+
+        let result_value = power_cts
+            .iter()
+            .zip(coeffs)
+            .fold(self.pow_g(constant), |acc, (c, coeff)| {
+                (acc * c.value().modpow(coeff, &self.n)) % &self.n
+            });
+        let operands: Vec<&Ciphertext> = power_cts.iter().collect();
+        Ok(self.homomorphic_result(result_value, &operands))
+    }
+
+    /// Computes the AND of an encrypted bit with a public bit.
+    /// Returns the ciphertext re-randomized if the public bit is set, otherwise a fresh
+    /// encryption of zero, so both cases look alike to an observer.
+    /// Only one operand can be private: the AND or OR of two encrypted bits needs a
+    /// multiplication of plaintexts, which is not possible without interaction.
+    pub fn homomorphic_and_public(
+        &self,
+        c: &Ciphertext,
+        public_bit: bool,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertext(c)?;
+
+        let zero = OkamotoUchiyama::encrypt(&BigUint::zero(), self);
+        let result_value = if public_bit {
+            (c.value() * zero.value()) % &self.n
+        } else {
+            zero.value().clone()
+        };
+        Ok(self.homomorphic_result(result_value, &[c]))
+    }
+
+    /// Computes the NOT of an encrypted bit, i.e. an encryption of 1 - m computed as
+    /// g * c^(-1) mod n.
+    /// It only behaves as a NOT when the plaintext is 0 or 1: any other plaintext m gives
+    /// 1 - m mod p. Returns an `InvalidCiphertext` error if c is not invertible mod n.
+    pub fn homomorphic_not(&self, bit: &Ciphertext) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertext(bit)?;
+
+        let inverse = invert_mod(bit.value(), &self.n)?;
+        let result_value = (&self.g * inverse) % &self.n;
+        Ok(self.homomorphic_result(result_value, &[bit]))
+    }
+
+    /// Adds `term` to `acc` if `include` is 1, or a fresh encryption of zero if it is 0,
+    /// without branching on the choice: the encryption of zero is always computed, the
+    /// operand is selected with `Ciphertext::conditional_select` and always multiplied in.
+    /// The result is then multiplied by a second fresh encryption of zero whatever the
+    /// choice, so it cannot be compared with acc * term to reveal the choice.
+    /// Note that the underlying BigUint arithmetic is not itself guaranteed to be constant-time.
+    #[cfg(feature = "subtle")]
+    pub fn homomorphic_add_conditional(
+        &self,
+        acc: &Ciphertext,
+        term: &Ciphertext,
+        include: subtle::Choice,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertexts([acc, term])?;
+
+        let zero = OkamotoUchiyama::encrypt(&BigUint::zero(), self);
+        let selected = Ciphertext::conditional_select(&zero, term, include);
+        let rerandomizer = OkamotoUchiyama::encrypt(&BigUint::zero(), self);
+        let result_value =
+            (acc.value() * selected.value() % &self.n) * rerandomizer.value() % &self.n;
+        Ok(self.homomorphic_result(result_value, &[acc, term]))
+    }
+
+    /// Aggregates client submissions for a weighted average.
+    /// Each client submits Enc(w_i) and Enc(w_i * x_i), and the server returns
+    /// (Enc(sum w_i), Enc(sum w_i * x_i)); the private key holder decrypts both and divides
+    /// the second by the first. Both slices must have the same length, otherwise a
+    /// `LengthMismatch` error is returned.
+    /// Overflow budget: sum w_i * x_i must stay below p, i.e. with k clients, weights below W
+    /// and values below X, k * W * X < p.
+    pub fn weighted_average_prep(
+        &self,
+        weights: &[Ciphertext],
+        weighted_values: &[Ciphertext],
+    ) -> Result<(Ciphertext, Ciphertext), OkamotoUchiyamaError> {
+        if weights.len() != weighted_values.len() {
+            return Err(OkamotoUchiyamaError::LengthMismatch);
+        }
+
+        Ok((
+            self.homomorphic_sum_of(weights)?,
+            self.homomorphic_sum_of(weighted_values)?,
+        ))
+    }
+
+    /// Precomputes the inverse of a subtrahend, to subtract it from many ciphertexts
+    pub fn subtraction_context(
+        &self,
+        subtrahend: &Ciphertext,
+    ) -> Result<SubtractionContext, OkamotoUchiyamaError> {
+        SubtractionContext::new(self, subtrahend)
+    }
+
+    /// Encrypts every message, in order, into a single `CIPHERTEXT BUNDLE` PEM string,
+    /// see `Ciphertext::bundle_to_pem`.
+    /// Returns an `InvalidKey` error if the key is uninitialized.
+    pub fn encrypt_many_to_bundle(
+        &self,
+        messages: &[BigUint],
+    ) -> Result<String, OkamotoUchiyamaError> {
+        if self.n.is_zero() {
+            return Err(OkamotoUchiyamaError::InvalidKey);
+        }
+
+        let ciphertexts: Vec<Ciphertext> = messages
+            .iter()
+            .map(|m| OkamotoUchiyama::encrypt(m, self))
+            .collect();
+        Ok(Ciphertext::bundle_to_pem(&ciphertexts))
+    }
+
+    /// Adds two ciphertexts and subtracts a reduction hint, i.e. computes an encryption of
+    /// m1 + m2 - k mod p where k is the plaintext of `bound_hint`.
+    /// This supports bounded aggregation: a client that knows m1 and m2 sends the encryption
+    /// of an amount k keeping the sum within an agreed bound (e.g. a multiple of a modulus
+    /// the aggregate is reduced by), since the server cannot reduce it homomorphically.
+    /// Trust model: the server only checks that every operand is a valid ciphertext for this
+    /// key. The client is trusted to provide a correct hint, which the holder of the private
+    /// key checks when decrypting the aggregate against the bound it was given out-of-band,
+    /// e.g. with `PrivateKey::decrypt_with_overflow_check`.
+    pub fn bounded_add(
+        &self,
+        c1: &Ciphertext,
+        c2: &Ciphertext,
+        bound_hint: &Ciphertext,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertexts([c1, c2, bound_hint])?;
+
+        // c1 * c2 * hint^(-1) mod n
+        let hint_inverse = invert_mod(bound_hint.value(), &self.n)?;
+        let result_value = (c1.value() * c2.value() % &self.n * hint_inverse) % &self.n;
+        Ok(self.homomorphic_result(result_value, &[c1, c2, bound_hint]))
+    }
+
+    /// Inserts an item into a counting Bloom filter of encrypted counters: the item is hashed
+    /// to `num_hashes` slots and a fresh encryption of one is added to each of them.
+    /// The filter is typically initialized with encryptions of zero; its counters can be
+    /// estimated by the private key holder with `PrivateKey::bloom_estimate`.
+    /// Returns a `LengthMismatch` error if the filter is empty or `num_hashes` is zero.
+    pub fn encrypted_bloom_increment(
+        &self,
+        filter: &mut [Ciphertext],
+        item: &[u8],
+        num_hashes: usize,
+    ) -> Result<(), OkamotoUchiyamaError> {
+        let slots = bloom_slots(item, num_hashes, filter.len())?;
+        self.check_ciphertexts(slots.iter().map(|&slot| &filter[slot]))?;
+
+        for slot in slots {
+            let one = OkamotoUchiyama::encrypt(&BigUint::one(), self);
+            let value = (filter[slot].value() * one.value()) % &self.n;
+            filter[slot] = self.homomorphic_result(value, &[&filter[slot]]);
+        }
+        Ok(())
+    }
+
+    /// Multiplies the plaintext of a ciphertext by a secret scalar, i.e. computes c^secret mod n.
+    /// Unlike `modpow`, the exponentiation uses a Montgomery ladder over a fixed number of bits
+    /// (at least the bit length of n), performing one multiplication and one squaring per bit
+    /// whatever the value of the secret, so its Hamming weight is not revealed by the
+    /// sequence of operations. The ladder registers are swapped through an XOR mask rather
+    /// than a branch on the secret bits.
+    /// Note that the underlying BigUint arithmetic is not itself guaranteed to be constant-time.
+    pub fn homomorphic_scale_secret(
+        &self,
+        c: &Ciphertext,
+        secret: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.check_ciphertext(c)?;
+
+        // Left-pad the secret to the byte length of n so every secret is processed
+        // over the same number of bits
+        let n_len = self.n.bits().div_ceil(8);
+        let secret_bytes = secret.to_bytes_be();
+        let mut padded = vec![0u8; n_len.saturating_sub(secret_bytes.len())];
+        padded.extend_from_slice(&secret_bytes);
+
+        // Montgomery ladder invariant: r1 = r0 * c mod n. The registers are swapped with a
+        // mask whenever the current bit differs from the previous one, instead of branching
+        let mut r0 = BigUint::one();
+        let mut r1 = c.value().clone();
+        let mut previous_bit = 0u8;
+        for byte in padded {
+            for i in (0..8).rev() {
+                let bit = (byte >> i) & 1;
+                conditional_swap(&mut r0, &mut r1, bit ^ previous_bit, n_len);
+                previous_bit = bit;
+                r1 = (&r0 * &r1) % &self.n;
+                r0 = (&r0 * &r0) % &self.n;
+            }
         }
-        let result_value = result % &self.n;
-        Ok(Ciphertext::new(result_value))
+        conditional_swap(&mut r0, &mut r1, previous_bit, n_len);
+
+        Ok(self.homomorphic_result(r0, &[c]))
+    }
+}
+
+// Swaps a and b if swap is 1 and leaves them if it is 0, without branching on swap:
+// both values are padded to len bytes and exchanged byte by byte through an XOR mask
+fn conditional_swap(a: &mut BigUint, b: &mut BigUint, swap: u8, len: usize) {
+    let pad = |value: &BigUint| {
+        let bytes = value.to_bytes_be();
+        let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+        padded.extend_from_slice(&bytes);
+        padded
+    };
+    let (mut a_bytes, mut b_bytes) = (pad(a), pad(b));
+
+    // 0x00 if swap is 0, 0xff if it is 1
+    let mask = 0u8.wrapping_sub(swap & 1);
+    for (a_byte, b_byte) in a_bytes.iter_mut().zip(b_bytes.iter_mut()) {
+        let t = mask & (*a_byte ^ *b_byte);
+        *a_byte ^= t;
+        *b_byte ^= t;
+    }
+
+    *a = BigUint::from_bytes_be(&a_bytes);
+    *b = BigUint::from_bytes_be(&b_bytes);
+}
+
+// Hashes an item to `num_hashes` slots of a Bloom filter of `len` counters,
+// slot i being SHA-256(i || item) reduced mod len
+pub(crate) fn bloom_slots(
+    item: &[u8],
+    num_hashes: usize,
+    len: usize,
+) -> Result<Vec<usize>, OkamotoUchiyamaError> {
+    if len == 0 || num_hashes == 0 {
+        return Err(OkamotoUchiyamaError::LengthMismatch);
     }
+
+    Ok((0..num_hashes as u64)
+        .map(|i| {
+            let digest = Sha256::new()
+                .chain_update(i.to_be_bytes())
+                .chain_update(item)
+                .finalize();
+            let mut prefix = [0u8; 8];
+            prefix.copy_from_slice(&digest[..8]);
+            (u64::from_be_bytes(prefix) % len as u64) as usize
+        })
+        .collect())
+}
+
+// Parses a flat JSON object whose values are strings without escapes or integers into its
+// (key, value) pairs, the quotes of string values removed. Returns None if malformed.
+fn parse_flat_json(json: &str) -> Option<Vec<(String, String)>> {
+    let body = json.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    if body.is_empty() {
+        return Some(Vec::new());
+    }
+
+    body.split(',')
+        .map(|member| {
+            let (key, value) = member.split_once(':')?;
+            let key = key.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted.strip_suffix('"')?,
+                None if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => value,
+                None => return None,
+            };
+            if key.contains(['"', '\\']) || value.contains(['"', '\\']) {
+                return None;
+            }
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// Computes the inverse of a value mod n
+pub(crate) fn invert_mod(value: &BigUint, n: &BigUint) -> Result<BigUint, OkamotoUchiyamaError> {
+    mod_inverse(
+        std::borrow::Cow::Borrowed(value),
+        std::borrow::Cow::Borrowed(n),
+    )
+    .and_then(|inverse| inverse.to_biguint())
+    .ok_or(OkamotoUchiyamaError::InvalidCiphertext)
 }
 
 // Implements Display trait for the PublicKey struct
@@ -141,29 +1142,9 @@ impl PemEncodable for PublicKey {
     fn to_pem(&self) -> String {
         let mut pem = String::new();
 
-        // Convert public key components to ASN.1
-        let n_bytes = self.n.clone().to_bytes_be();
-        let n_asn1 = Asn1BigUint::new(&n_bytes);
-
-        let g_bytes = self.g.clone().to_bytes_be();
-        let g_asn1 = Asn1BigUint::new(&g_bytes);
-
-        let h_bytes = self.h.clone().to_bytes_be();
-        let h_asn1 = Asn1BigUint::new(&h_bytes);
-
-        // Write all elements to ASN.1 Sequence
-        let result = asn1::write(|w| {
-            w.write_element(&asn1::SequenceWriter::new(&|w| {
-                w.write_element(&n_asn1)?; // Add n to the sequence
-                w.write_element(&g_asn1)?; // Add g to the sequence
-                w.write_element(&h_asn1)?; // Add h to the sequence
-                Ok(())
-            }))
-        });
-
         // Encode the ASN.1 sequence using Base64
         pem.push_str("-----BEGIN PUBLIC KEY-----\n");
-        pem.push_str(&general_purpose::STANDARD.encode(result.unwrap_or_else(|_| vec![])));
+        pem.push_str(&general_purpose::STANDARD.encode(self.to_der()));
         pem.push_str("\n-----END PUBLIC KEY-----\n");
 
         pem
@@ -0,0 +1,32 @@
+use hmac::{Hmac, Mac};
+use num_bigint_dig::BigUint;
+use sha2::Sha256;
+use std::time::SystemTime;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A record of a decryption, for audit trails.
+/// It identifies which ciphertext was decrypted and when, and holds no secret material:
+/// neither the plaintext nor any value derived from the private key in the clear.
+/// The commitment to the decryption intermediate is keyed with a fresh random nonce, so
+/// receipts of equal plaintexts cannot be linked. Only the private key holder, who can
+/// recompute the intermediate from the ciphertext, can check it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecryptReceipt {
+    // SHA-256 fingerprint of the decrypted ciphertext
+    pub ciphertext_fingerprint: [u8; 32],
+    // Time at which the decryption was performed
+    pub timestamp: SystemTime,
+    // Random nonce keying the commitment, drawn for each receipt
+    pub nonce: [u8; 32],
+    // HMAC-SHA256 under the nonce of the intermediate value a = c^(p-1) mod p^2,
+    // None if the ciphertext was rejected before decryption
+    pub exponentiation_commitment: Option<[u8; 32]>,
+}
+
+// Commits to the intermediate value a under the nonce of a receipt
+pub(crate) fn commit_exponentiation(nonce: &[u8; 32], a: &BigUint) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(nonce).expect("HMAC accepts any key length");
+    mac.update(&a.to_bytes_be());
+    mac.finalize().into_bytes().into()
+}
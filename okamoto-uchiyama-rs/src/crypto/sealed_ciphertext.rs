@@ -0,0 +1,65 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::error::OkamotoUchiyamaError;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A ciphertext carrying an HMAC-SHA256 tag over its DER encoding, created by
+/// `Ciphertext::seal`. Any change to the ciphertext value is detected by `open`.
+/// Sealing defeats the homomorphism on purpose: a sealed ciphertext must be opened before
+/// operating on it, and the result of an operation has to be sealed again.
+/// The key tag of the ciphertext is not authenticated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedCiphertext {
+    // The sealed ciphertext
+    ciphertext: Ciphertext,
+    // HMAC-SHA256 tag over the DER encoding of the ciphertext
+    tag: [u8; 32],
+}
+
+impl SealedCiphertext {
+    /// Seals a ciphertext under a MAC key of any length
+    pub fn new(ciphertext: &Ciphertext, mac_key: &[u8]) -> Self {
+        SealedCiphertext {
+            ciphertext: ciphertext.clone(),
+            tag: compute_tag(ciphertext, mac_key)
+                .finalize()
+                .into_bytes()
+                .into(),
+        }
+    }
+
+    /// Assembles a sealed ciphertext from stored parts, to be verified by `open`
+    pub fn from_parts(ciphertext: Ciphertext, tag: [u8; 32]) -> Self {
+        SealedCiphertext { ciphertext, tag }
+    }
+
+    /// Verifies the tag under the MAC key, in constant time, and returns the ciphertext.
+    /// Returns an `InvalidTag` error if the ciphertext or the tag was tampered with, or if
+    /// the MAC key is not the one it was sealed with.
+    pub fn open(&self, mac_key: &[u8]) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        compute_tag(&self.ciphertext, mac_key)
+            .verify_slice(&self.tag)
+            .map_err(|_| OkamotoUchiyamaError::InvalidTag)?;
+        Ok(self.ciphertext.clone())
+    }
+
+    /// Returns the sealed ciphertext, without verifying it
+    pub fn ciphertext_unverified(&self) -> &Ciphertext {
+        &self.ciphertext
+    }
+
+    /// Returns the authentication tag
+    pub fn tag(&self) -> &[u8; 32] {
+        &self.tag
+    }
+}
+
+// Starts an HMAC-SHA256 over the DER encoding of a ciphertext
+fn compute_tag(ciphertext: &Ciphertext, mac_key: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(mac_key).expect("HMAC accepts any key length");
+    mac.update(&ciphertext.to_der());
+    mac
+}
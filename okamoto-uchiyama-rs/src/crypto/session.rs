@@ -0,0 +1,82 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+
+use num_bigint_dig::BigUint;
+
+/// A homomorphic computation bound to a single public key.
+/// Every ciphertext encrypted through the session is tagged with the fingerprint of the
+/// key, cached at creation, so mixing in a ciphertext tagged by another key fails with a
+/// `KeyMismatch` error instead of silently producing garbage.
+#[derive(Debug, Clone)]
+pub struct HomomorphicSession<'a> {
+    // The public key all the ciphertexts of the session are encrypted with
+    public_key: &'a PublicKey,
+    // Cached fingerprint of the public key
+    key_id: [u8; 32],
+}
+
+impl<'a> HomomorphicSession<'a> {
+    /// Opens a session bound to the given public key
+    pub fn new(public_key: &'a PublicKey) -> Self {
+        HomomorphicSession {
+            public_key,
+            key_id: public_key.fingerprint(),
+        }
+    }
+
+    /// Returns the public key the session is bound to
+    pub fn public_key(&self) -> &'a PublicKey {
+        self.public_key
+    }
+
+    /// Encrypts a message, tagging the ciphertext with the session key
+    pub fn encrypt(&self, message: &BigUint) -> Ciphertext {
+        let ciphertext = OkamotoUchiyama::encrypt(message, self.public_key);
+        Ciphertext::tagged(ciphertext.value().clone(), self.key_id)
+    }
+
+    /// Adds the plaintexts of two ciphertexts
+    pub fn add(
+        &self,
+        c1: &Ciphertext,
+        c2: &Ciphertext,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.public_key.homomorphic_encrypt_two(c1, c2)
+    }
+
+    /// Adds the plaintexts of multiple ciphertexts
+    pub fn add_multiple(&self, ciphers: &[Ciphertext]) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.public_key.homomorphic_sum_of(ciphers)
+    }
+
+    /// Adds a public constant to the plaintext of a ciphertext
+    pub fn add_constant(
+        &self,
+        c: &Ciphertext,
+        k: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        let constant = self.public_key.encrypt_constant(k);
+        self.public_key.homomorphic_encrypt_two(c, &constant)
+    }
+
+    /// Multiplies the plaintext of a ciphertext by a public constant, i.e. computes c^k mod n
+    pub fn multiply_constant(
+        &self,
+        c: &Ciphertext,
+        k: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.public_key.multiply_constant(c, k)
+    }
+
+    /// Multiplies the plaintext of a ciphertext by a secret scalar,
+    /// see `PublicKey::homomorphic_scale_secret`
+    pub fn scale_secret(
+        &self,
+        c: &Ciphertext,
+        secret: &BigUint,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.public_key.homomorphic_scale_secret(c, secret)
+    }
+}
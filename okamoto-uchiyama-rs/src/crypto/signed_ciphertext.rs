@@ -0,0 +1,92 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::okamoto_uchiyama::OkamotoUchiyama;
+use crate::crypto::public_key::{invert_mod, PublicKey};
+use crate::error::OkamotoUchiyamaError;
+
+use num::{Signed, Zero};
+use num_bigint_dig::{BigInt, BigUint};
+
+/// A ciphertext of a signed integer.
+/// A negative message m is encrypted as g^m = (g^|m|)^(-1) mod n, so it decrypts to
+/// p - |m|. Decryption maps plaintexts above p / 2 back to negative values, hence the
+/// representable range is (-p/2, p/2): any result outside of it wraps around.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCiphertext {
+    // The underlying ciphertext
+    ciphertext: Ciphertext,
+}
+
+impl SignedCiphertext {
+    /// Encrypts a signed message under a public key.
+    /// Returns an `InvalidCiphertext` error if the ciphertext of a negative message cannot be
+    /// inverted mod n, which only happens with a malformed public key.
+    pub fn encrypt(message: &BigInt, public_key: &PublicKey) -> Result<Self, OkamotoUchiyamaError> {
+        let ciphertext = OkamotoUchiyama::encrypt(&magnitude(message), public_key);
+        if message.is_negative() {
+            // Invert the ciphertext to negate the plaintext
+            let inverse = invert_mod(ciphertext.value(), &public_key.n)?;
+            return Ok(SignedCiphertext::from(
+                public_key.homomorphic_result(inverse, &[&ciphertext]),
+            ));
+        }
+        Ok(SignedCiphertext::from(ciphertext))
+    }
+
+    /// Returns the underlying ciphertext
+    pub fn ciphertext(&self) -> &Ciphertext {
+        &self.ciphertext
+    }
+
+    /// Performs the homomorphic addition of two signed ciphertexts
+    pub fn homomorphic_add(
+        &self,
+        other: &SignedCiphertext,
+        public_key: &PublicKey,
+    ) -> Result<SignedCiphertext, OkamotoUchiyamaError> {
+        public_key
+            .homomorphic_encrypt_two(&self.ciphertext, &other.ciphertext)
+            .map(SignedCiphertext::from)
+    }
+
+    /// Performs the homomorphic subtraction of two signed ciphertexts, i.e. c1 * c2^(-1) mod n
+    pub fn homomorphic_subtract(
+        &self,
+        other: &SignedCiphertext,
+        public_key: &PublicKey,
+    ) -> Result<SignedCiphertext, OkamotoUchiyamaError> {
+        public_key.check_ciphertext(&other.ciphertext)?;
+        let inverse = invert_mod(other.ciphertext.value(), &public_key.n)?;
+        self.homomorphic_add(
+            &SignedCiphertext::from(public_key.homomorphic_result(inverse, &[&other.ciphertext])),
+            public_key,
+        )
+    }
+
+    /// Multiplies the plaintext by a signed public constant, i.e. computes c^k mod n
+    pub fn multiply_constant(
+        &self,
+        k: &BigInt,
+        public_key: &PublicKey,
+    ) -> Result<SignedCiphertext, OkamotoUchiyamaError> {
+        public_key.check_ciphertext(&self.ciphertext)?;
+        let mut value = self.ciphertext.value().modpow(&magnitude(k), &public_key.n);
+        if k.is_negative() {
+            value = invert_mod(&value, &public_key.n)?;
+        }
+        Ok(SignedCiphertext::from(
+            public_key.homomorphic_result(value, &[&self.ciphertext]),
+        ))
+    }
+}
+
+// Wraps an unsigned ciphertext, its plaintext being interpreted as signed
+impl From<Ciphertext> for SignedCiphertext {
+    fn from(ciphertext: Ciphertext) -> Self {
+        SignedCiphertext { ciphertext }
+    }
+}
+
+// Returns the absolute value of a BigInt as a BigUint
+fn magnitude(value: &BigInt) -> BigUint {
+    value.abs().to_biguint().unwrap_or_else(BigUint::zero)
+}
@@ -0,0 +1,44 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::public_key::{invert_mod, PublicKey};
+use crate::error::OkamotoUchiyamaError;
+
+use num_bigint_dig::BigUint;
+
+/// Holds the precomputed inverse of a fixed subtrahend c2.
+/// Subtracting m2 from the plaintext of c1 is computing c1 * c2^(-1) mod n. The modular
+/// inverse dominates the cost of a subtraction, so when the same c2 is subtracted from many
+/// ciphertexts (e.g. the mean in deviation computations) it is only computed once.
+/// The differences are reduced mod p, so negative differences wrap around to p - |m1 - m2|.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtractionContext {
+    // The public key the ciphertexts are encrypted with
+    public_key: PublicKey,
+    // c2^(-1) mod n
+    inverse: BigUint,
+}
+
+impl SubtractionContext {
+    /// Precomputes the inverse of the subtrahend under the given public key
+    pub fn new(
+        public_key: &PublicKey,
+        subtrahend: &Ciphertext,
+    ) -> Result<Self, OkamotoUchiyamaError> {
+        public_key.check_ciphertext(subtrahend)?;
+
+        Ok(SubtractionContext {
+            public_key: public_key.clone(),
+            inverse: invert_mod(subtrahend.value(), &public_key.n)?,
+        })
+    }
+
+    /// Subtracts the fixed subtrahend from the plaintext of a ciphertext
+    pub fn homomorphic_subtract_fast(
+        &self,
+        c1: &Ciphertext,
+    ) -> Result<Ciphertext, OkamotoUchiyamaError> {
+        self.public_key.check_ciphertext(c1)?;
+
+        let result_value = (c1.value() * &self.inverse) % &self.public_key.n;
+        Ok(Ciphertext::new(result_value))
+    }
+}
@@ -0,0 +1,107 @@
+use num::One;
+use num_bigint_dig::BigUint;
+use std::fmt;
+
+// Table for fixed-base exponentiation of a base b mod n with w-bit windows:
+// powers[i][d] = b^(d * 2^(w * i)) mod n, for each window i of the exponent and digit d.
+// b^e is then the product of powers[i][e_i] over the windows e_i of e, which needs one
+// multiplication per window and no squaring.
+#[derive(Clone)]
+pub(crate) struct FixedBaseTable {
+    window_bits: u32,
+    powers: Vec<Vec<BigUint>>,
+}
+
+impl FixedBaseTable {
+    // Builds the table for exponents up to max_exponent_bits bits
+    pub(crate) fn new(
+        base: &BigUint,
+        n: &BigUint,
+        window_bits: u32,
+        max_exponent_bits: usize,
+    ) -> Self {
+        let windows = max_exponent_bits.div_ceil(window_bits as usize);
+        let mut powers = Vec::with_capacity(windows);
+
+        // b^(2^(w * i)) for the current window
+        let mut window_base = base % n;
+        for _ in 0..windows {
+            let mut row = Vec::with_capacity(1 << window_bits);
+            let mut power = BigUint::one() % n;
+            for _ in 0..(1u32 << window_bits) {
+                let next = (&power * &window_base) % n;
+                row.push(power);
+                power = next;
+            }
+            // b^(2^(w * (i + 1))) = b^(2^w * 2^(w * i))
+            window_base = power;
+            powers.push(row);
+        }
+
+        FixedBaseTable {
+            window_bits,
+            powers,
+        }
+    }
+
+    // Computes b^exponent mod n, or None if the exponent is too large for the table
+    pub(crate) fn pow(&self, exponent: &BigUint, n: &BigUint) -> Option<BigUint> {
+        let window_bits = self.window_bits as usize;
+        let windows = exponent.bits().div_ceil(window_bits);
+        if windows > self.powers.len() {
+            return None;
+        }
+
+        // Split the exponent into little-endian windows of window_bits bits
+        let bytes = exponent.to_bytes_le();
+        let bit = |i: usize| {
+            bytes
+                .get(i / 8)
+                .is_some_and(|byte| (byte >> (i % 8)) & 1 == 1)
+        };
+
+        let mut result = BigUint::one() % n;
+        for (i, row) in self.powers.iter().take(windows).enumerate() {
+            let digit = (0..window_bits)
+                .filter(|&j| bit(i * window_bits + j))
+                .fold(0usize, |digit, j| digit | (1 << j));
+            if digit != 0 {
+                result = (&result * &row[digit]) % n;
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Precomputed tables speeding up encryption under a fixed public key,
+/// see `PublicKey::precompute_encryption_tables`
+#[derive(Clone)]
+pub struct EncryptionTables {
+    // The n, g and h the tables were built for
+    pub(crate) key: (BigUint, BigUint, BigUint),
+    // Table of powers of g
+    pub(crate) g: FixedBaseTable,
+    // Table of powers of h
+    pub(crate) h: FixedBaseTable,
+}
+
+impl EncryptionTables {
+    // Checks that the tables were built for these key components
+    pub(crate) fn built_for(&self, n: &BigUint, g: &BigUint, h: &BigUint) -> bool {
+        (&self.key.0, &self.key.1, &self.key.2) == (n, g, h)
+    }
+
+    /// Returns the window size in bits of the tables
+    pub fn window_bits(&self) -> u32 {
+        self.g.window_bits
+    }
+}
+
+// Only prints the window size, the tables themselves are large
+impl fmt::Debug for EncryptionTables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionTables")
+            .field("window_bits", &self.window_bits())
+            .finish()
+    }
+}
@@ -0,0 +1,12 @@
+use num_bigint_dig::BigUint;
+
+/// A homomorphic operation recorded in an aggregation transcript, see
+/// `PrivateKey::verify_transcript`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HomomorphicOp {
+    // Sum of the plaintexts of the inputs, the product of the ciphertexts mod n
+    Sum,
+    // Sum of the plaintexts of the inputs scaled by public weights, given in the order of
+    // the inputs, the product of c_i^w_i mod n
+    WeightedSum { weights: Vec<BigUint> },
+}
@@ -6,15 +6,131 @@ pub enum OkamotoUchiyamaError {
     #[error("Message is larger than public key size")]
     MessageTooLarge,
 
+    // When the result of a homomorphic operation could wrap past the plaintext space
+    #[error("Homomorphic result may exceed the plaintext space")]
+    PlaintextOverflow,
+
+    // When a decrypted count exceeds the number of counted values
+    #[error("Count exceeds the number of counted values")]
+    CountOutOfRange,
+
+    // When columns of ciphertexts do not all have the same length
+    #[error("Columns do not all have the same length")]
+    RaggedColumns,
+
+    // When the lengths of related inputs do not match
+    #[error("Input lengths do not match")]
+    LengthMismatch,
+
+    // When a decrypted plaintext is suspiciously close to p, suggesting it wrapped around
+    #[error("Decrypted plaintext likely wrapped around the plaintext space")]
+    LikelyWrapped,
+
+    // When a constant has no inverse modulo p
+    #[error("Constant is not invertible modulo p")]
+    NotInvertible,
+
+    // When CRT moduli share a common factor
+    #[error("Moduli are not pairwise coprime")]
+    ModuliNotCoprime,
+
+    // When a divisor is zero
+    #[error("Divisor must not be zero")]
+    InvalidDivisor,
+
     // When the ciphertext is too large for the public key size
-    #[error("Message is larger than public key size")]
+    #[error("Ciphertext is larger than public key size")]
     CipherTooLarge,
 
-    // When the PEM key decoding fails
-    #[error("Error when decoding the PEM encoded key")]
-    PemDecodingError,
+    // When the ciphertext cannot be valid under the public key (zero or not coprime with n)
+    #[error("Ciphertext is not a valid element for this public key")]
+    InvalidCiphertext,
+
+    // When a ciphertext of a batch is not valid under the public key
+    #[error("Ciphertext at index {0} is not valid for this public key")]
+    InvalidCiphertextAt(usize),
+
+    // When a tagged ciphertext was encrypted under another public key
+    #[error("Ciphertext was encrypted under another public key")]
+    KeyMismatch,
+
+    // When a published fingerprint does not match the key it describes
+    #[error("Fingerprint does not match the key")]
+    FingerprintMismatch,
+
+    // When a key is missing components or is structurally invalid
+    #[error("Invalid or uninitialized key")]
+    InvalidKey,
+
+    // When the requested prime sizes do not meet the security heuristic
+    #[error("Key size is insecure: {0}")]
+    InsecureKeySize(String),
+
+    // When the requested key size is too small to generate the primes
+    #[error("Key size is too small to generate the primes")]
+    InvalidKeySize,
+
+    // When the PEM key decoding fails, with the underlying error when there is one
+    #[error("Error when decoding the PEM encoded key{}", detail(.0))]
+    PemDecodingError(#[source] Option<Box<OkamotoUchiyamaError>>),
+
+    // When the authentication tag of a sealed ciphertext does not verify
+    #[error("Authentication tag does not match the ciphertext")]
+    InvalidTag,
+
+    // When the checksum of a checked PEM does not match its content
+    #[error("PEM checksum does not match its content")]
+    ChecksumMismatch,
+
+    // When an encrypted PEM cannot be decrypted with the given password
+    #[error("Wrong password or corrupted encrypted key")]
+    InvalidPassword,
+
+    // When a decoded ASN.1 sequence does not have the expected number of elements
+    #[error("ASN.1 sequence has {found} elements, expected {expected}")]
+    ElementCountMismatch { expected: usize, found: usize },
+
+    // When decoding serialized (non-PEM) data fails
+    #[error("Error when decoding serialized data")]
+    DecodingError,
+
+    // When an input could not be parsed as any known type, with the error of each attempt
+    #[error("Input does not match any known format: {0:?}")]
+    NoFormatMatched(Vec<OkamotoUchiyamaError>),
+
+    // When a decryption guard has exhausted the decryptions allowed for the current period
+    #[error("Too many decryptions for the current period")]
+    RateLimited,
+
+    // When a line of a line-based input cannot be processed
+    #[error("Line {0} is not a valid input")]
+    InvalidLine(usize),
+
+    // When reading or writing a stream fails
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    // When a batch holds more terms than the accepted limit
+    #[error("Batch has more terms than the accepted limit")]
+    BatchTooLarge,
+
+    // When a sliding window is empty or longer than the sequence
+    #[error("Window size must be between 1 and the number of ciphertexts")]
+    InvalidWindow,
+
+    // When a serialized input exceeds the accepted size
+    #[error("Input is larger than the accepted size")]
+    InputTooLarge,
 
     // Generic error message
     #[error("Okamoto-Uchiyama failed with the following stdout: {stdout} stderr: {stderr}")]
     OkamotoUchiyamaError { stdout: String, stderr: String },
 }
+
+// Formats the underlying error of a PEM decoding error, if any, as a suffix of its message
+fn detail(source: &Option<Box<OkamotoUchiyamaError>>) -> String {
+    source
+        .as_ref()
+        .map(|source| format!(": {}", source))
+        .unwrap_or_default()
+}
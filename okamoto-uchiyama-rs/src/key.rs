@@ -1,8 +1,116 @@
+use crate::error::OkamotoUchiyamaError;
+
+/// Smallest bit size allowed for either of the primes p and q
+pub const MIN_PRIME_BITS: usize = 16;
+
+/// Smallest bit size of either prime accepted by `KeyGenOptions::validate_security`
+pub const MIN_SECURE_PRIME_BITS: usize = 256;
+
+/// Smallest bit size of n = p^2 * q accepted by `KeyGenOptions::validate_security`
+pub const MIN_SECURE_MODULUS_BITS: usize = 1024;
+
+/// Number of Miller-Rabin rounds run by the prime generator on each prime candidate
+pub const MILLER_RABIN_ROUNDS: u32 = 8;
+
 /// Allowed key sizes are
-/// 512, 1024, 2048 and 4096 bits
+/// 512, 1024, 2048 and 4096 bits,
+/// or a custom size in bits for experimentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeySize {
     Bits512,
     Bits1024,
     Bits2048,
     Bits4096,
+    Custom(u32),
+}
+
+impl KeySize {
+    /// Returns the key size in bits
+    pub fn bits(&self) -> u32 {
+        match self {
+            KeySize::Bits512 => 512,
+            KeySize::Bits1024 => 1024,
+            KeySize::Bits2048 => 2048,
+            KeySize::Bits4096 => 4096,
+            KeySize::Custom(bits) => *bits,
+        }
+    }
+
+    /// Returns the bit sizes `(p_bits, q_bits)` of the primes generated for this key size.
+    /// Errors with `InvalidKeySize` if either prime would be smaller than `MIN_PRIME_BITS`.
+    pub fn prime_sizes(&self) -> Result<(usize, usize), OkamotoUchiyamaError> {
+        let bits =
+            usize::try_from(self.bits()).map_err(|_| OkamotoUchiyamaError::InvalidKeySize)?;
+
+        // p is sized at a third of the key size, q at half of it
+        let p_bits = bits / 3;
+        let q_bits = bits / 2;
+
+        if p_bits < MIN_PRIME_BITS || q_bits < MIN_PRIME_BITS {
+            return Err(OkamotoUchiyamaError::InvalidKeySize);
+        }
+
+        Ok((p_bits, q_bits))
+    }
+}
+
+/// Explicit bit sizes of the primes p and q, for `OkamotoUchiyama::init_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyGenOptions {
+    // Bit size of p, which also bounds the plaintext space
+    pub p_bits: usize,
+    // Bit size of q
+    pub q_bits: usize,
+}
+
+impl KeyGenOptions {
+    /// Creates options generating a p of `p_bits` bits and a q of `q_bits` bits
+    pub fn new(p_bits: usize, q_bits: usize) -> Self {
+        KeyGenOptions { p_bits, q_bits }
+    }
+
+    /// Returns the bit size of n = p^2 * q
+    pub fn modulus_bits(&self) -> usize {
+        2 * self.p_bits + self.q_bits
+    }
+
+    /// Checks the split against a heuristic for the hardness of factoring n = p^2 * q.
+    /// The best known attacks are the number field sieve, whose cost grows with the size
+    /// of n, and the elliptic curve method, whose cost grows with the size of the smallest
+    /// prime factor; no known method does better on p^2 * q than on an RSA modulus of the
+    /// same size. Hence the heuristic requires:
+    /// - n of at least `MIN_SECURE_MODULUS_BITS` bits,
+    /// - p and q of at least `MIN_SECURE_PRIME_BITS` bits, out of reach of ECM,
+    /// - p and q within a factor of 2 of each other in bit size, since n is only as hard to
+    ///   factor as its smallest factor is to find.
+    ///
+    /// The original scheme takes p and q of the same size. Returns an `InsecureKeySize`
+    /// error describing the first requirement that is not met.
+    pub fn validate_security(&self) -> Result<(), OkamotoUchiyamaError> {
+        let modulus_bits = self.modulus_bits();
+        if modulus_bits < MIN_SECURE_MODULUS_BITS {
+            return Err(OkamotoUchiyamaError::InsecureKeySize(format!(
+                "n has {} bits, at least {} are required",
+                modulus_bits, MIN_SECURE_MODULUS_BITS
+            )));
+        }
+
+        for (name, bits) in [("p", self.p_bits), ("q", self.q_bits)] {
+            if bits < MIN_SECURE_PRIME_BITS {
+                return Err(OkamotoUchiyamaError::InsecureKeySize(format!(
+                    "{} has {} bits, at least {} are required",
+                    name, bits, MIN_SECURE_PRIME_BITS
+                )));
+            }
+        }
+
+        if self.p_bits > 2 * self.q_bits || self.q_bits > 2 * self.p_bits {
+            return Err(OkamotoUchiyamaError::InsecureKeySize(format!(
+                "p has {} bits and q has {} bits, their sizes must be within a factor of 2",
+                self.p_bits, self.q_bits
+            )));
+        }
+
+        Ok(())
+    }
 }
@@ -1,5 +1,419 @@
+use crate::crypto::ciphertext::Ciphertext;
+use crate::crypto::private_key::PrivateKey;
+use crate::crypto::public_key::PublicKey;
+use crate::error::OkamotoUchiyamaError;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+use num_bigint_dig::BigUint;
+
+/// Default upper bound, in bytes, on the decoded body of a PEM input accepted by `from_pem`.
+/// 64 KiB is far larger than any key or ciphertext of a supported key size, while keeping
+/// untrusted inputs from causing large allocations. Use `from_pem_limited` to pick another bound.
+pub const DEFAULT_MAX_PEM_BYTES: usize = 64 * 1024;
+
+/// Number of PBKDF2-HMAC-SHA256 iterations used to derive the key of an encrypted PEM
+pub const PBKDF2_ITERATIONS: u32 = 100_000;
+
+// Upper bound on the iterations accepted when reading an encrypted PEM
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+// Upper bound on the bytes read by the `load` functions, leaving room for the base64
+// expansion and the line breaks of a PEM body of `DEFAULT_MAX_PEM_BYTES` bytes
+const MAX_LOAD_BYTES: usize = 2 * DEFAULT_MAX_PEM_BYTES;
+
 /// A trait for types that can be encoded into PEM (Privacy Enhanced Mail) format.
 pub trait PemEncodable {
     /// Converts the implementor into a PEM-encoded string
     fn to_pem(&self) -> String;
+
+    /// Converts the implementor into a PEM-encoded string carrying a `Checksum:` header,
+    /// the first 8 bytes of the SHA-256 digest of the DER bytes, verified by `from_pem_checked`
+    fn to_pem_checked(&self) -> String {
+        add_checksum(&self.to_pem())
+    }
+}
+
+/// An object decoded from a PEM block, see `parse_all`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PemObject {
+    PublicKey(PublicKey),
+    PrivateKey(PrivateKey),
+    Ciphertext(Ciphertext),
+}
+
+/// Parses every PEM block of a string, e.g. a file mixing keys and ciphertexts,
+/// dispatching on the armor label (`PUBLIC KEY`, `PRIVATE KEY` or `CIPHERTEXT`).
+/// Blocks carrying a `Checksum:` header are verified as with `from_pem_checked`.
+/// Blocks with any other label (e.g. `ENCRYPTED PRIVATE KEY`) and text outside of the
+/// blocks are ignored; an unterminated block or a malformed recognized block is an error.
+pub fn parse_all(pem: &str) -> Result<Vec<PemObject>, OkamotoUchiyamaError> {
+    let mut objects = Vec::new();
+    let mut lines = pem.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        let Some(label) = line
+            .strip_prefix("-----BEGIN ")
+            .and_then(|rest| rest.strip_suffix("-----"))
+        else {
+            continue;
+        };
+
+        // Collect the block up to its END line
+        let end = format!("-----END {}-----", label);
+        let mut block = vec![line];
+        loop {
+            let line = lines
+                .next()
+                .ok_or(OkamotoUchiyamaError::PemDecodingError(None))?;
+            block.push(line);
+            if line == end {
+                break;
+            }
+        }
+        let block = unwrap_body(&block.join("\n"));
+        let checked = block
+            .lines()
+            .nth(1)
+            .is_some_and(|line| line.starts_with("Checksum: "));
+
+        let object = match (label, checked) {
+            ("PUBLIC KEY", false) => PemObject::PublicKey(PublicKey::from_pem(&block)?),
+            ("PUBLIC KEY", true) => PemObject::PublicKey(PublicKey::from_pem_checked(&block)?),
+            ("PRIVATE KEY", false) => PemObject::PrivateKey(PrivateKey::from_pem(&block)?),
+            ("PRIVATE KEY", true) => PemObject::PrivateKey(PrivateKey::from_pem_checked(&block)?),
+            ("CIPHERTEXT", false) => PemObject::Ciphertext(Ciphertext::from_pem(&block)?),
+            ("CIPHERTEXT", true) => PemObject::Ciphertext(Ciphertext::from_pem_checked(&block)?),
+            // Unrecognized blocks are skipped
+            _ => continue,
+        };
+        objects.push(object);
+    }
+
+    Ok(objects)
+}
+
+/// Parses a single PEM block whatever its armor label, to handle mislabeled files: its body
+/// is tried as a `PublicKey`, a `PrivateKey`, then a `Ciphertext`, and the first success is
+/// returned. A `Checksum:` header is verified as with `from_pem_checked`. If every type
+/// fails, a `NoFormatMatched` error holds the three errors, in that order.
+/// The label is the only statement of intent of a PEM block: ignoring it means trusting the
+/// structure of the body alone, so a block meant as one type but well-formed as an earlier
+/// type of the list is returned as the latter. Prefer `parse_all` when labels are reliable.
+pub fn try_parse_any(pem: &str) -> Result<PemObject, OkamotoUchiyamaError> {
+    let unwrapped = unwrap_body(pem);
+    let lines: Vec<&str> = unwrapped.lines().collect();
+    if lines.len() < 3
+        || !lines[0].starts_with("-----BEGIN ")
+        || !lines[lines.len() - 1].starts_with("-----END ")
+    {
+        return Err(OkamotoUchiyamaError::PemDecodingError(None));
+    }
+
+    // Rebuild the block with the label expected by each parser
+    let inner = lines[1..lines.len() - 1].join("\n");
+    let checked = lines[1].starts_with("Checksum:");
+    let relabel =
+        |label: &str| format!("-----BEGIN {label}-----\n{inner}\n-----END {label}-----\n");
+
+    let mut errors = Vec::new();
+    let public_key = relabel("PUBLIC KEY");
+    match if checked {
+        PublicKey::from_pem_checked(&public_key)
+    } else {
+        PublicKey::from_pem(&public_key)
+    } {
+        Ok(public_key) => return Ok(PemObject::PublicKey(public_key)),
+        Err(error) => errors.push(error),
+    }
+    let private_key = relabel("PRIVATE KEY");
+    match if checked {
+        PrivateKey::from_pem_checked(&private_key)
+    } else {
+        PrivateKey::from_pem(&private_key)
+    } {
+        Ok(private_key) => return Ok(PemObject::PrivateKey(private_key)),
+        Err(error) => errors.push(error),
+    }
+    let ciphertext = relabel("CIPHERTEXT");
+    match if checked {
+        Ciphertext::from_pem_checked(&ciphertext)
+    } else {
+        Ciphertext::from_pem(&ciphertext)
+    } {
+        Ok(ciphertext) => return Ok(PemObject::Ciphertext(ciphertext)),
+        Err(error) => errors.push(error),
+    }
+
+    Err(OkamotoUchiyamaError::NoFormatMatched(errors))
+}
+
+/// Checks whether two PEM strings encode the same ciphertext, whatever the wrapping of
+/// their base64 body and the surrounding whitespace.
+/// Returns an error if either of them is not a valid ciphertext PEM.
+pub fn ciphertext_pem_equal(a: &str, b: &str) -> Result<bool, OkamotoUchiyamaError> {
+    let a = Ciphertext::from_pem(&unwrap_body(a))?;
+    let b = Ciphertext::from_pem(&unwrap_body(b))?;
+    Ok(a == b)
+}
+
+/// Checks whether two PEM strings encode the same sequence of keys and ciphertexts, as
+/// decoded by `parse_all`, whatever the wrapping of their base64 bodies.
+/// Returns an error if either of them contains a malformed recognized block.
+pub fn pem_objects_equal(a: &str, b: &str) -> Result<bool, OkamotoUchiyamaError> {
+    Ok(parse_all(a)? == parse_all(b)?)
+}
+
+// Joins the lines of a wrapped base64 body into one, as expected by the `from_pem`
+// functions. The armor lines and header lines such as `Checksum:` are kept as they are.
+fn unwrap_body(pem: &str) -> String {
+    let lines: Vec<&str> = pem
+        .trim()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.len() < 3 {
+        return pem.to_string();
+    }
+
+    let (headers, body): (Vec<&str>, Vec<&str>) = lines[1..lines.len() - 1]
+        .iter()
+        .partition(|line| line.contains(':'));
+
+    let mut unwrapped = vec![lines[0]];
+    unwrapped.extend(headers);
+    let body = body.concat();
+    unwrapped.push(&body);
+    unwrapped.push(lines[lines.len() - 1]);
+    unwrapped.join("\n")
+}
+
+// An input read by the `load` functions, with its detected encoding
+pub(crate) enum Detected {
+    // PEM armor, as produced by `to_pem`
+    Pem(String),
+    // PEM armor carrying a `Checksum:` header, as produced by `to_pem_checked`
+    CheckedPem(String),
+    // Anything else, handed to the DER parser of the loaded type
+    Der(Vec<u8>),
+}
+
+// Reads an input of at most `MAX_LOAD_BYTES` bytes and detects its encoding.
+// PEM starts with `-----BEGIN ` once leading whitespace is skipped, and is checked when its
+// second line is a `Checksum:` header. DER starts with the tag of its outer element (0x30 for
+// a SEQUENCE, 0x02 for an INTEGER), which can never be mistaken for the PEM armor, so any
+// other input is treated as DER and left to the parser to reject.
+pub(crate) fn read_detected(reader: impl Read) -> Result<Detected, OkamotoUchiyamaError> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_LOAD_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|_| OkamotoUchiyamaError::DecodingError)?;
+    if bytes.len() > MAX_LOAD_BYTES {
+        return Err(OkamotoUchiyamaError::InputTooLarge);
+    }
+
+    if !bytes.trim_ascii_start().starts_with(b"-----BEGIN ") {
+        return Ok(Detected::Der(bytes));
+    }
+
+    let pem = String::from_utf8(bytes).map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+    let checked = pem
+        .trim()
+        .lines()
+        .nth(1)
+        .is_some_and(|line| line.trim().starts_with("Checksum: "));
+
+    Ok(if checked {
+        Detected::CheckedPem(pem)
+    } else {
+        Detected::Pem(pem)
+    })
+}
+
+// Reports a failure to decode the DER body of a PEM input as a PEM decoding error,
+// keeping the element counts of a malformed SEQUENCE as its source.
+pub(crate) fn pem_decoding_error(error: OkamotoUchiyamaError) -> OkamotoUchiyamaError {
+    match error {
+        OkamotoUchiyamaError::DecodingError => OkamotoUchiyamaError::PemDecodingError(None),
+        OkamotoUchiyamaError::ElementCountMismatch { .. } => {
+            OkamotoUchiyamaError::PemDecodingError(Some(Box::new(error)))
+        }
+        error => error,
+    }
+}
+
+/// Converts a BigUint to the big-endian bytes of a DER INTEGER.
+/// A leading zero byte is added when the most significant bit is set,
+/// otherwise the value would be read as negative and rejected by `asn1::BigUint::new`.
+pub(crate) fn to_asn1_bytes(value: &BigUint) -> Vec<u8> {
+    let mut bytes = value.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+    bytes
+}
+
+// Rejects a base64 body whose decoded size exceeds max_bytes, before decoding it.
+// The decoded size is computed from the length of the body without its padding.
+pub(crate) fn check_decoded_size(
+    base64_encoded: &str,
+    max_bytes: usize,
+) -> Result<(), OkamotoUchiyamaError> {
+    let decoded_size = base64_encoded.trim_end_matches('=').len() * 3 / 4;
+    if decoded_size > max_bytes {
+        return Err(OkamotoUchiyamaError::InputTooLarge);
+    }
+    Ok(())
+}
+
+// Computes the hex-encoded checksum of DER bytes: the first 8 bytes of their SHA-256 digest
+fn checksum(der: &[u8]) -> String {
+    Sha256::digest(der)[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Inserts a checksum header after the BEGIN line of a PEM string
+fn add_checksum(pem: &str) -> String {
+    let lines: Vec<&str> = pem.trim().lines().collect();
+    let body = lines[1..lines.len() - 1].concat();
+    let der = STANDARD.decode(&body).unwrap_or_default();
+
+    format!(
+        "{}\nChecksum: {}\n{}\n{}\n",
+        lines[0],
+        checksum(&der),
+        body,
+        lines[lines.len() - 1]
+    )
+}
+
+// Verifies the checksum header of a PEM string and returns the PEM string without it.
+// Returns a `ChecksumMismatch` error if the DER bytes do not match the checksum.
+pub(crate) fn verify_checksum(pem: &str) -> Result<String, OkamotoUchiyamaError> {
+    let lines: Vec<&str> = pem.trim().lines().map(str::trim).collect();
+    if lines.len() < 4 {
+        return Err(OkamotoUchiyamaError::PemDecodingError(None));
+    }
+
+    // The checksum header directly follows the BEGIN line
+    let expected = lines[1]
+        .strip_prefix("Checksum: ")
+        .ok_or(OkamotoUchiyamaError::PemDecodingError(None))?;
+
+    let body = lines[2..lines.len() - 1].concat();
+    check_decoded_size(&body, DEFAULT_MAX_PEM_BYTES)?;
+    let der = STANDARD
+        .decode(&body)
+        .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+    if !constant_time_eq(checksum(&der).as_bytes(), expected.as_bytes()) {
+        return Err(OkamotoUchiyamaError::ChecksumMismatch);
+    }
+
+    Ok(format!(
+        "{}\n{}\n{}\n",
+        lines[0],
+        body,
+        lines[lines.len() - 1]
+    ))
+}
+
+// Compares two byte strings in time independent of their content, so that verifying an
+// integrity value does not reveal how many leading bytes of a forgery were right.
+// Only the lengths, which are public, may end the comparison early.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+// Derives an AES-256 key from a password with PBKDF2-HMAC-SHA256
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+// Encrypts DER bytes with AES-256-GCM under a key derived from a password.
+// The container is a DER SEQUENCE {salt, iterations, nonce, ciphertext}, the salt and the
+// iterations being authenticated as associated data.
+pub(crate) fn encrypt_der(der: &[u8], password: &str) -> Vec<u8> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt, PBKDF2_ITERATIONS);
+    let aad = [&salt[..], &PBKDF2_ITERATIONS.to_be_bytes()].concat();
+    let ciphertext = Aes256Gcm::new(&key.into())
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: der,
+                aad: &aad,
+            },
+        )
+        .unwrap_or_default();
+
+    // Write all elements to ASN.1 Sequence
+    let result = asn1::write(|w| {
+        w.write_element(&asn1::SequenceWriter::new(&|w| {
+            w.write_element(&&salt[..])?; // Add the salt to the sequence
+            w.write_element(&PBKDF2_ITERATIONS)?; // Add the iterations to the sequence
+            w.write_element(&&nonce[..])?; // Add the nonce to the sequence
+            w.write_element(&ciphertext.as_slice())?; // Add the ciphertext to the sequence
+            Ok(())
+        }))
+    });
+
+    result.unwrap_or_else(|_| vec![])
+}
+
+// Decrypts a container produced by `encrypt_der`.
+// Returns an `InvalidPassword` error if the authentication fails.
+pub(crate) fn decrypt_der(
+    container: &[u8],
+    password: &str,
+) -> Result<Vec<u8>, OkamotoUchiyamaError> {
+    // Parse the ASN.1 sequence
+    let (salt, iterations, nonce, ciphertext) =
+        asn1::parse::<_, asn1::ParseError, _>(container, |d: &mut asn1::Parser<'_>| {
+            d.read_element::<asn1::Sequence>()?
+                .parse::<_, asn1::ParseError, _>(|d| {
+                    let salt = d.read_element::<&[u8]>()?;
+                    let iterations = d.read_element::<u32>()?;
+                    let nonce = d.read_element::<&[u8]>()?;
+                    let ciphertext = d.read_element::<&[u8]>()?;
+                    Ok((salt, iterations, nonce, ciphertext))
+                })
+        })
+        .map_err(|_| OkamotoUchiyamaError::PemDecodingError(None))?;
+
+    if nonce.len() != 12 || iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(OkamotoUchiyamaError::PemDecodingError(None));
+    }
+
+    let key = derive_key(password, salt, iterations);
+    let aad = [salt, &iterations.to_be_bytes()].concat();
+    Aes256Gcm::new(&key.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| OkamotoUchiyamaError::InvalidPassword)
 }
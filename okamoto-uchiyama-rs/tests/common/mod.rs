@@ -0,0 +1,56 @@
+// Shared fixtures for the integration tests.
+// Each test binary only uses part of these helpers.
+#![allow(dead_code)]
+
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey, PublicKey};
+use std::sync::OnceLock;
+use std::thread;
+
+/// Generates a fresh private key of the given size
+pub fn generate_private_key(key_size: KeySize) -> PrivateKey {
+    OkamotoUchiyama::init(key_size)
+        .unwrap()
+        .generate_private_key()
+        .unwrap()
+}
+
+/// Returns a 1024 bits private key generated once per test binary
+pub fn shared_private_key() -> &'static PrivateKey {
+    static PRIVATE_KEY: OnceLock<PrivateKey> = OnceLock::new();
+    PRIVATE_KEY.get_or_init(|| generate_private_key(KeySize::Bits1024))
+}
+
+/// Returns the public key of the shared private key
+pub fn shared_public_key() -> &'static PublicKey {
+    &shared_private_key().public_key
+}
+
+/// Generates one private key per requested size, concurrently
+pub fn generate_all_sizes(key_sizes: &[KeySize]) -> Vec<(KeySize, PrivateKey)> {
+    thread::scope(|s| {
+        let handles: Vec<_> = key_sizes
+            .iter()
+            .map(|&key_size| s.spawn(move || (key_size, generate_private_key(key_size))))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+/// Small fixed public key (p = 2003, q = 2351)
+pub fn small_public_key() -> PublicKey {
+    PublicKey::new(
+        &num_bigint_dig::BigUint::from(9432233159u64),
+        &num_bigint_dig::BigUint::from(8083706871u64),
+        &num_bigint_dig::BigUint::from(7988052977u64),
+    )
+}
+
+/// Small fixed private key matching `small_public_key`
+pub fn small_private_key() -> PrivateKey {
+    PrivateKey::new(
+        &small_public_key(),
+        &num_bigint_dig::BigUint::from(2003u64),
+        &num_bigint_dig::BigUint::from(2351u64),
+    )
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::accumulator::HomomorphicAccumulator;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::{OkamotoUchiyama, PublicKey};
+
+#[test]
+fn test_accumulator_checkpoint() {
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let ciphertexts: Vec<_> = (1..=10u64)
+        .map(|m| OkamotoUchiyama::encrypt(&BigUint::from(m), public_key))
+        .collect();
+
+    // Uninterrupted run
+    let mut uninterrupted = HomomorphicAccumulator::new(public_key);
+    for c in &ciphertexts {
+        uninterrupted.add(c).unwrap();
+    }
+
+    // Run split across a checkpoint
+    let mut first_half = HomomorphicAccumulator::new(public_key);
+    for c in &ciphertexts[..5] {
+        first_half.add(c).unwrap();
+    }
+    let checkpoint = first_half.to_bytes();
+
+    let mut resumed = HomomorphicAccumulator::from_bytes(&checkpoint, public_key).unwrap();
+    assert_eq!(resumed.count(), 5);
+    for c in &ciphertexts[5..] {
+        resumed.add(c).unwrap();
+    }
+
+    assert_eq!(resumed.count(), 10);
+    assert_eq!(resumed.total(), uninterrupted.total());
+
+    // 1 + 2 + ... + 10 = 55
+    let sum = OkamotoUchiyama::decrypt(&resumed.total(), private_key);
+    assert_eq!(sum, BigUint::from(55u64));
+
+    // The checkpoint cannot be resumed under another key
+    let other_key = PublicKey::new(
+        &BigUint::from(9432233159u64),
+        &BigUint::from(8083706871u64),
+        &BigUint::from(7988052977u64),
+    );
+    assert!(matches!(
+        HomomorphicAccumulator::from_bytes(&checkpoint, &other_key),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+
+    // Garbage is rejected
+    assert!(matches!(
+        HomomorphicAccumulator::from_bytes(&[0x30, 0x01], public_key),
+        Err(OkamotoUchiyamaError::DecodingError)
+    ));
+}
+
+#[test]
+fn test_accumulator_rerandomize_every() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let mut plain = HomomorphicAccumulator::new(public_key);
+    let mut masked = HomomorphicAccumulator::new(public_key).with_rerandomize_every(2);
+    let values = [3u64, 14, 15, 92, 65, 35, 89];
+    for (i, &value) in values.iter().enumerate() {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(value), public_key);
+        plain.add(&c).unwrap();
+        masked.add(&c).unwrap();
+
+        // The totals diverge from the first rerandomization, after the second addition
+        if i == 0 {
+            assert_eq!(masked.total(), plain.total());
+        } else {
+            assert_ne!(masked.total(), plain.total());
+        }
+        assert_eq!(
+            OkamotoUchiyama::decrypt(&masked.total(), private_key),
+            OkamotoUchiyama::decrypt(&plain.total(), private_key)
+        );
+    }
+
+    assert_eq!(masked.count(), values.len() as u64);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&masked.total(), private_key),
+        BigUint::from(values.iter().sum::<u64>())
+    );
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::bounded_public_key::BoundedPublicKey;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_bounded_public_key_from_key_generation() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let bounded_public_key = okamoto_uchiyama.generate_bounded_public_key().unwrap();
+
+    // The bound is a lower bound on p
+    assert!(bounded_public_key.plaintext_bound <= private_key.p);
+    assert_eq!(bounded_public_key.public_key, private_key.public_key);
+
+    // The largest message below the bound decrypts correctly
+    let message = &bounded_public_key.plaintext_bound - 1u32;
+    let ciphertext = bounded_public_key.encrypt(&message).unwrap();
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+
+    // Messages at or above the bound are rejected
+    for message in [
+        bounded_public_key.plaintext_bound.clone(),
+        private_key.p.clone(),
+    ] {
+        assert!(matches!(
+            bounded_public_key.encrypt(&message),
+            Err(OkamotoUchiyamaError::MessageTooLarge)
+        ));
+    }
+}
+
+#[test]
+fn test_bounded_public_key_with_explicit_bound() {
+    // 1024 <= p = 2003
+    let bounded_public_key =
+        BoundedPublicKey::new(&common::small_public_key(), &BigUint::from(1024u64));
+    let private_key = common::small_private_key();
+
+    let ciphertext = bounded_public_key.encrypt(&BigUint::from(1000u64)).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&ciphertext, &private_key),
+        BigUint::from(1000u64)
+    );
+
+    assert!(matches!(
+        bounded_public_key.encrypt(&BigUint::from(1024u64)),
+        Err(OkamotoUchiyamaError::MessageTooLarge)
+    ));
+}
+
+#[test]
+fn test_homomorphic_encrypt_two_checked() {
+    // 1024 <= p = 2003
+    let bounded_public_key =
+        BoundedPublicKey::new(&common::small_public_key(), &BigUint::from(1024u64));
+    let private_key = common::small_private_key();
+
+    let c1 = bounded_public_key.encrypt(&BigUint::from(500u64)).unwrap();
+    let c2 = bounded_public_key.encrypt(&BigUint::from(510u64)).unwrap();
+
+    // 2 * 512 = 1024 reaches the bound, the sum could overflow
+    assert!(matches!(
+        bounded_public_key.homomorphic_encrypt_two_checked(&c1, &c2, &BigUint::from(512u64)),
+        Err(OkamotoUchiyamaError::PlaintextOverflow)
+    ));
+
+    // 2 * 511 = 1022 stays below the bound
+    let sum = bounded_public_key
+        .homomorphic_encrypt_two_checked(&c1, &c2, &BigUint::from(511u64))
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, &private_key),
+        BigUint::from(1010u64)
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::counters::EncryptedCounters;
+
+#[test]
+fn test_encrypted_counters() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let mut counters = EncryptedCounters::new(public_key);
+    counters.increment("visits", 5).unwrap();
+    counters.increment("visits", 3).unwrap();
+    counters.decrement("visits", 2).unwrap();
+    counters.increment("errors", 1).unwrap();
+    counters.decrement("stock", 0).unwrap();
+    counters.increment("stock", 100).unwrap();
+    counters.decrement("stock", 42).unwrap();
+    assert!(counters.get("missing").is_none());
+
+    // Adjusting a counter changes its ciphertext
+    let before = counters.get("errors").unwrap().clone();
+    counters.increment("errors", 0).unwrap();
+    assert_ne!(counters.get("errors").unwrap(), &before);
+
+    let values = private_key.read_counters(&counters).unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values["visits"], BigUint::from(6u32));
+    assert_eq!(values["errors"], BigUint::from(1u32));
+    assert_eq!(values["stock"], BigUint::from(58u32));
+
+    // A counter created by a decrement wraps around below zero
+    counters.decrement("balance", 1).unwrap();
+    let values = private_key.read_counters(&counters).unwrap();
+    assert_eq!(values["balance"], &private_key.p - 1u32);
+}
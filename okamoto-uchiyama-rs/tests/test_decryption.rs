@@ -1,19 +1,45 @@
+mod common;
+
 use num_bigint_dig::BigUint;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::key::KeySize;
 use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey, PublicKey};
 
 #[test]
 fn test_encryption_decryption() {
     let message = BigUint::from(1337u64);
 
-    // Initialization
-    let length = okamoto_uchiyama::key::KeySize::Bits1024;
-    let okamoto_uchiyama = OkamotoUchiyama::init(length);
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
 
-    // Generate the key pair
-    let private_key = okamoto_uchiyama.generate_private_key();
-    let public_key = private_key.public_key.clone();
+    let ciphertext = OkamotoUchiyama::encrypt(&message, public_key);
+    let plaintext: BigUint = OkamotoUchiyama::decrypt(&ciphertext, private_key);
 
-    let ciphertext = OkamotoUchiyama::encrypt(&message, &public_key);
+    assert_eq!(message, plaintext);
+}
+
+#[test]
+fn test_encryption_decryption_all_sizes() {
+    let message = BigUint::from(1337u64);
+
+    // Key pairs are generated concurrently
+    let key_sizes = [KeySize::Bits512, KeySize::Bits1024, KeySize::Bits2048];
+    for (key_size, private_key) in common::generate_all_sizes(&key_sizes) {
+        let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+        let plaintext: BigUint = OkamotoUchiyama::decrypt(&ciphertext, &private_key);
+
+        assert_eq!(message, plaintext, "round-trip failed for {:?}", key_size);
+    }
+}
+
+#[test]
+#[ignore = "generating a 4096 bits key takes a while, run with --ignored"]
+fn test_encryption_decryption_4096_bits() {
+    let message = BigUint::from(1337u64);
+
+    let private_key = common::generate_private_key(KeySize::Bits4096);
+    let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
     let plaintext: BigUint = OkamotoUchiyama::decrypt(&ciphertext, &private_key);
 
     assert_eq!(message, plaintext);
@@ -39,3 +65,21 @@ fn test_encryption_decryption_from_public_key() {
 
     assert_eq!(message, plaintext);
 }
+
+#[test]
+fn test_decrypt_mod() {
+    let private_key = common::shared_private_key();
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(17u64), &private_key.public_key);
+
+    assert_eq!(
+        private_key
+            .decrypt_mod(&ciphertext, &BigUint::from(5u64))
+            .unwrap(),
+        BigUint::from(2u64)
+    );
+
+    assert!(matches!(
+        private_key.decrypt_mod(&ciphertext, &BigUint::from(0u64)),
+        Err(OkamotoUchiyamaError::InvalidDivisor)
+    ));
+}
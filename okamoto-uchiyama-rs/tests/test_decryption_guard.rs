@@ -0,0 +1,52 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::decryption_guard::DecryptionGuard;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_decryption_guard_limits_decryptions() {
+    let private_key = common::small_private_key();
+    let message = BigUint::from(1337u64);
+    let c = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+
+    let mut guard = DecryptionGuard::new(&private_key, 3, Duration::from_secs(3600));
+    for remaining in (0..3).rev() {
+        assert_eq!(guard.decrypt(&c).unwrap(), message);
+        assert_eq!(guard.remaining(), remaining);
+    }
+    assert!(matches!(
+        guard.decrypt(&c),
+        Err(OkamotoUchiyamaError::RateLimited)
+    ));
+}
+
+#[test]
+fn test_decryption_guard_resets_each_period() {
+    let private_key = common::small_private_key();
+    let message = BigUint::from(1337u64);
+    let c = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+
+    let mut guard = DecryptionGuard::new(&private_key, 1, Duration::from_millis(50));
+
+    // Rejected ciphertexts do not count
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    assert!(matches!(
+        guard.decrypt(&zero),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+
+    assert_eq!(guard.decrypt(&c).unwrap(), message);
+    assert!(matches!(
+        guard.decrypt(&c),
+        Err(OkamotoUchiyamaError::RateLimited)
+    ));
+
+    thread::sleep(Duration::from_millis(60));
+    assert_eq!(guard.remaining(), 1);
+    assert_eq!(guard.decrypt(&c).unwrap(), message);
+}
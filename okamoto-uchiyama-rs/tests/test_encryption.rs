@@ -1,25 +1,29 @@
-use num_bigint_dig::BigUint;
-use okamoto_uchiyama::OkamotoUchiyama;
+mod common;
+
+use num::One;
+use num_bigint_dig::{BigUint, ModInverse};
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::okamoto_uchiyama::EncryptPolicy;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::{OkamotoUchiyama, PublicKey};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 #[test]
 fn test_homomorphic_encrypt_two() {
     let m1 = BigUint::from(6u64);
     let m2 = BigUint::from(7u64);
 
-    // Initialization
-    let length = okamoto_uchiyama::key::KeySize::Bits1024;
-    let okamoto_uchiyama = OkamotoUchiyama::init(length);
-
-    // Generate the key pair
-    let private_key = okamoto_uchiyama.generate_private_key();
-    let public_key = private_key.public_key.clone();
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
 
-    let c1 = OkamotoUchiyama::encrypt(&m1, &public_key);
-    let c2 = OkamotoUchiyama::encrypt(&m2, &public_key);
+    let c1 = OkamotoUchiyama::encrypt(&m1, public_key);
+    let c2 = OkamotoUchiyama::encrypt(&m2, public_key);
 
     let c1_c2 = public_key.homomorphic_encrypt_two(&c1, &c2).unwrap();
 
-    let decrypted_c1_c2 = OkamotoUchiyama::decrypt(&c1_c2, &private_key);
+    let decrypted_c1_c2 = OkamotoUchiyama::decrypt(&c1_c2, private_key);
     assert_eq!(decrypted_c1_c2, BigUint::from(13u64))
 }
 
@@ -29,22 +33,890 @@ fn test_homomorphic_encrypt_multiple() {
     let m2 = BigUint::from(7u64);
     let m3 = BigUint::from(8u64);
 
-    // Initialization
-    let length = okamoto_uchiyama::key::KeySize::Bits1024;
-    let okamoto_uchiyama = OkamotoUchiyama::init(length);
-
-    // Generate the key pair
-    let private_key = okamoto_uchiyama.generate_private_key();
-    let public_key = private_key.public_key.clone();
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
 
-    let c1 = OkamotoUchiyama::encrypt(&m1, &public_key);
-    let c2 = OkamotoUchiyama::encrypt(&m2, &public_key);
-    let c3 = OkamotoUchiyama::encrypt(&m3, &public_key);
+    let c1 = OkamotoUchiyama::encrypt(&m1, public_key);
+    let c2 = OkamotoUchiyama::encrypt(&m2, public_key);
+    let c3 = OkamotoUchiyama::encrypt(&m3, public_key);
 
     let c1_c2_c3 = public_key
         .homomorphic_encrypt_multiple(vec![&c1, &c2, &c3])
         .unwrap();
 
-    let decrypted_c1_c2_c3 = OkamotoUchiyama::decrypt(&c1_c2_c3, &private_key);
+    let decrypted_c1_c2_c3 = OkamotoUchiyama::decrypt(&c1_c2_c3, private_key);
     assert_eq!(decrypted_c1_c2_c3, BigUint::from(21u64))
 }
+
+#[test]
+fn test_add_assign_with_key() {
+    let private_key = common::small_private_key();
+    let public_key = &private_key.public_key;
+
+    let ciphertexts: Vec<Ciphertext> = (0..1000u64)
+        .map(|i| OkamotoUchiyama::encrypt(&BigUint::from(i % 2), public_key))
+        .collect();
+
+    // Accumulate in place
+    let mut accumulated = ciphertexts[0].clone();
+    for c in &ciphertexts[1..] {
+        accumulated.add_assign_with_key(c, public_key).unwrap();
+    }
+
+    let batched = public_key
+        .homomorphic_encrypt_multiple(ciphertexts.iter().collect())
+        .unwrap();
+    assert_eq!(accumulated, batched);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&accumulated, &private_key),
+        BigUint::from(500u64)
+    );
+
+    // An invalid operand leaves the accumulator unchanged
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    assert!(matches!(
+        accumulated.add_assign_with_key(&zero, public_key),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+    assert_eq!(accumulated, batched);
+}
+
+#[test]
+fn test_homomorphic_scale_secret() {
+    let m = BigUint::from(6u64);
+
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let c = OkamotoUchiyama::encrypt(&m, public_key);
+
+    // Secrets with low and high Hamming weights follow the same code path, from zero to
+    // secrets as long as n, whose bits are all set or all but the top one clear, and
+    // secrets longer than n, which lengthen the ladder
+    let n_bits = public_key.n.bits();
+    let all_ones = |bits: usize| (BigUint::one() << bits) - 1u32;
+    let secrets = [0u64, 1, 7, 0x80, 0xFF, 0x8000_0000, 0xFFFF_FFFF]
+        .map(BigUint::from)
+        .into_iter()
+        .chain([
+            BigUint::one() << (n_bits - 1),
+            all_ones(n_bits),
+            &public_key.n - 1u32,
+            BigUint::one() << (n_bits + 64),
+            all_ones(n_bits + 64),
+        ]);
+    for secret in secrets {
+        let scaled = public_key.homomorphic_scale_secret(&c, &secret).unwrap();
+
+        // The ladder matches a plain modpow
+        assert_eq!(scaled.value(), &c.value().modpow(&secret, &public_key.n));
+
+        let decrypted = OkamotoUchiyama::decrypt(&scaled, private_key);
+        assert_eq!(decrypted, (&m * &secret) % &private_key.p);
+    }
+}
+
+#[test]
+fn test_encrypt_with_context() {
+    let m = BigUint::from(1337u64);
+
+    // Shared key pair
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // The same rng state is used for both contexts
+    let c1 = public_key.encrypt_with_context(&m, b"context-a", &mut StdRng::seed_from_u64(42));
+    let c2 = public_key.encrypt_with_context(&m, b"context-b", &mut StdRng::seed_from_u64(42));
+    assert_ne!(c1, c2);
+
+    // Same context and rng state gives the same ciphertext
+    let c3 = public_key.encrypt_with_context(&m, b"context-a", &mut StdRng::seed_from_u64(42));
+    assert_eq!(c1, c3);
+
+    // Both decrypt correctly
+    assert_eq!(OkamotoUchiyama::decrypt(&c1, private_key), m);
+    assert_eq!(OkamotoUchiyama::decrypt(&c2, private_key), m);
+}
+
+#[test]
+fn test_encrypt_column() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Column of 1000 values
+    let column: Vec<BigUint> = (1..=1000u64).map(BigUint::from).collect();
+    let encrypted = public_key.encrypt_column(column.clone());
+    assert_eq!(encrypted.len(), 1000);
+
+    // Order is preserved
+    let decrypted: Vec<BigUint> = private_key.decrypt_column(&encrypted).collect();
+    assert_eq!(decrypted, column);
+
+    // Homomorphically sum the column into a single aggregate
+    let aggregate = public_key
+        .homomorphic_encrypt_multiple(encrypted.iter().collect())
+        .unwrap();
+    let sum = OkamotoUchiyama::decrypt(&aggregate, private_key);
+    assert_eq!(sum, BigUint::from(500500u64));
+}
+
+#[test]
+fn test_encrypt_tagged() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let other_key = common::small_public_key();
+
+    let c1 = public_key.encrypt_tagged(&BigUint::from(20u64));
+    let c2 = public_key.encrypt_tagged(&BigUint::from(22u64));
+    assert_eq!(c1.key_id(), Some(&public_key.fingerprint()));
+
+    // The tag is not part of the equality
+    assert_eq!(Ciphertext::new(c1.value().clone()), c1);
+
+    // Ciphertexts tagged with the same key can be combined, and the result stays tagged
+    let sum = public_key.homomorphic_encrypt_two(&c1, &c2).unwrap();
+    assert_eq!(sum.key_id(), Some(&public_key.fingerprint()));
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, private_key),
+        BigUint::from(42u64)
+    );
+
+    // Untagged ciphertexts keep working
+    let untagged = OkamotoUchiyama::encrypt(&BigUint::from(8u64), public_key);
+    assert!(untagged.key_id().is_none());
+    let sum = public_key.homomorphic_encrypt_two(&c1, &untagged).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, private_key),
+        BigUint::from(28u64)
+    );
+
+    // Combining with a ciphertext tagged by another key fails
+    let foreign = other_key.encrypt_tagged(&BigUint::from(1u64));
+    assert!(matches!(
+        public_key.homomorphic_encrypt_two(&c1, &foreign),
+        Err(OkamotoUchiyamaError::KeyMismatch)
+    ));
+    assert!(matches!(
+        public_key.homomorphic_encrypt_multiple(vec![&c1, &c2, &foreign]),
+        Err(OkamotoUchiyamaError::KeyMismatch)
+    ));
+}
+
+#[test]
+fn test_encrypt_deterministic_from() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let m1 = BigUint::from(1337u64);
+    let m2 = BigUint::from(1338u64);
+
+    // Same seed and message give the same ciphertext
+    let c1 = OkamotoUchiyama::encrypt_deterministic_from(&m1, public_key, b"vector-1");
+    let c1_again = OkamotoUchiyama::encrypt_deterministic_from(&m1, public_key, b"vector-1");
+    assert_eq!(c1, c1_again);
+
+    // Different messages or seeds give different ciphertexts
+    let c2 = OkamotoUchiyama::encrypt_deterministic_from(&m2, public_key, b"vector-1");
+    let c3 = OkamotoUchiyama::encrypt_deterministic_from(&m1, public_key, b"vector-2");
+    assert_ne!(c1, c2);
+    assert_ne!(c1, c3);
+
+    // All of them decrypt correctly
+    assert_eq!(OkamotoUchiyama::decrypt(&c1, private_key), m1);
+    assert_eq!(OkamotoUchiyama::decrypt(&c2, private_key), m2);
+    assert_eq!(OkamotoUchiyama::decrypt(&c3, private_key), m1);
+
+    // Pairs with the same seed || message bytes, "ab" || "c" and "a" || "bc", get
+    // different randomness: h^r = c * g^(-m) mod n differs
+    let h_r = |m: u64, seed: &[u8]| {
+        let m = BigUint::from(m);
+        let c = OkamotoUchiyama::encrypt_deterministic_from(&m, public_key, seed);
+        let g_m = public_key.g.modpow(&m, &public_key.n);
+        let g_m_inverse = g_m
+            .mod_inverse(&public_key.n)
+            .unwrap()
+            .to_biguint()
+            .unwrap();
+        c.value() * g_m_inverse % &public_key.n
+    };
+    assert_ne!(h_r(0x63, b"ab"), h_r(0x6263, b"a"));
+}
+
+#[test]
+fn test_variance_prep() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let values = [3u64, 7, 7, 19, 24, 1];
+
+    // Each client submits Enc(x) and Enc(x^2)
+    let submissions: Vec<_> = values
+        .iter()
+        .map(|&x| {
+            (
+                OkamotoUchiyama::encrypt(&BigUint::from(x), public_key),
+                OkamotoUchiyama::encrypt(&BigUint::from(x * x), public_key),
+            )
+        })
+        .collect();
+
+    let (sum, sum_of_squares) = public_key.variance_prep(&submissions).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, private_key),
+        BigUint::from(values.iter().sum::<u64>())
+    );
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum_of_squares, private_key),
+        BigUint::from(values.iter().map(|x| x * x).sum::<u64>())
+    );
+
+    // homomorphic_sum_of gives the same aggregate as variance_prep
+    let encrypted_values: Vec<_> = submissions.iter().map(|(x, _)| x.clone()).collect();
+    assert_eq!(
+        public_key.homomorphic_sum_of(&encrypted_values).unwrap(),
+        sum
+    );
+}
+
+#[test]
+fn test_homomorphic_sum_columns() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let plaintext_columns: [Vec<u64>; 3] = [
+        vec![1, 2, 3, 4],
+        vec![10, 20, 30, 40],
+        vec![100, 200, 300, 400],
+    ];
+    let columns: Vec<Vec<_>> = plaintext_columns
+        .iter()
+        .map(|column| public_key.encrypt_column(column.iter().map(|&m| BigUint::from(m))))
+        .collect();
+
+    // One aggregate per column
+    let sums = public_key.homomorphic_sum_columns(&columns).unwrap();
+    assert_eq!(sums.len(), 3);
+    for (sum, column) in sums.iter().zip(&plaintext_columns) {
+        assert_eq!(
+            OkamotoUchiyama::decrypt(sum, private_key),
+            BigUint::from(column.iter().sum::<u64>())
+        );
+    }
+
+    // Ragged columns are rejected
+    let mut ragged = columns.clone();
+    ragged[1].pop();
+    assert!(matches!(
+        public_key.homomorphic_sum_columns(&ragged),
+        Err(OkamotoUchiyamaError::RaggedColumns)
+    ));
+}
+
+#[test]
+fn test_encrypt_constant() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let k = BigUint::from(1000u64);
+    let constant = public_key.encrypt_constant(&k);
+
+    // Deterministic: g^k mod n
+    assert_eq!(constant, public_key.encrypt_constant(&k));
+    assert_eq!(constant.value(), &public_key.g.modpow(&k, &public_key.n));
+
+    // Multiplying a ciphertext by the encrypted constant adds k to the plaintext
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(337u64), public_key);
+    let sum = public_key
+        .homomorphic_encrypt_two(&ciphertext, &constant)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, private_key),
+        BigUint::from(1337u64)
+    );
+}
+
+#[test]
+fn test_encrypt_into_reused_buffers() {
+    let private_key = common::small_private_key();
+    let public_key = &private_key.public_key;
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut value = BigUint::default();
+    let mut bytes = Vec::new();
+    for m in 0..200u64 {
+        let message = BigUint::from(m);
+
+        public_key.encrypt_into(&message, &mut value, &mut rng);
+        let ciphertext = Ciphertext::new(value.clone());
+        assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+
+        public_key.encrypt_bytes_into(&message, &mut bytes);
+        let ciphertext = Ciphertext::new(BigUint::from_bytes_be(&bytes));
+        assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+    }
+}
+
+#[test]
+fn test_encrypt_random() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let mut rng = StdRng::seed_from_u64(42);
+    let bound = BigUint::from(1u64) << 64;
+
+    let (mask_ciphertext, mask) = public_key.encrypt_random(&bound, &mut rng);
+    assert!(mask < bound);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&mask_ciphertext, private_key),
+        mask
+    );
+
+    // Adding then subtracting the mask leaves the plaintext unchanged
+    let message = BigUint::from(1337u64);
+    let c = OkamotoUchiyama::encrypt(&message, public_key);
+    let masked = public_key
+        .homomorphic_encrypt_two(&c, &mask_ciphertext)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&masked, private_key),
+        &message + &mask
+    );
+    let unmasked = public_key
+        .subtraction_context(&mask_ciphertext)
+        .unwrap()
+        .homomorphic_subtract_fast(&masked)
+        .unwrap();
+    assert_eq!(OkamotoUchiyama::decrypt(&unmasked, private_key), message);
+}
+
+#[test]
+fn test_encrypt_reduced() {
+    let private_key = common::small_private_key();
+    let public_key = &private_key.public_key;
+
+    // p = 2003, 5 * 2003 + 42 reduces to 42
+    let m = BigUint::from(5u64 * 2003 + 42);
+    let ciphertext = public_key.encrypt_reduced(&m, &private_key);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&ciphertext, &private_key),
+        &m % &private_key.p
+    );
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&ciphertext, &private_key),
+        BigUint::from(42u64)
+    );
+
+    // Messages below p are unchanged
+    let m = BigUint::from(1337u64);
+    let ciphertext = public_key.encrypt_reduced(&m, &private_key);
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), m);
+}
+
+#[test]
+fn test_is_likely_unrandomized() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Encryptions of small constants have no randomness
+    for k in [0u64, 1, 1000] {
+        assert!(public_key
+            .encrypt_constant(&BigUint::from(k))
+            .is_likely_unrandomized(public_key));
+    }
+
+    // Randomized encryptions are not flagged
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(1000u64), public_key);
+    assert!(!ciphertext.is_likely_unrandomized(public_key));
+}
+
+#[test]
+fn test_homomorphic_and_public() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    for private_bit in [0u64, 1] {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(private_bit), public_key);
+        for public_bit in [false, true] {
+            let result = public_key.homomorphic_and_public(&c, public_bit).unwrap();
+            assert_ne!(result, c);
+            assert_eq!(
+                OkamotoUchiyama::decrypt(&result, private_key),
+                BigUint::from(private_bit & public_bit as u64)
+            );
+        }
+    }
+
+    // The key tag is kept whatever the public bit
+    let tagged = public_key.encrypt_tagged(&BigUint::from(1u64));
+    for public_bit in [false, true] {
+        let result = public_key
+            .homomorphic_and_public(&tagged, public_bit)
+            .unwrap();
+        assert_eq!(result.key_id(), Some(&public_key.fingerprint()));
+    }
+
+    // Invalid ciphertexts are rejected like in the other homomorphic operations
+    assert!(matches!(
+        public_key.homomorphic_and_public(&Ciphertext::new(BigUint::from(0u32)), true),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_homomorphic_not() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    for bit in [0u64, 1] {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(bit), public_key);
+        let not = public_key.homomorphic_not(&c).unwrap();
+        assert_eq!(
+            OkamotoUchiyama::decrypt(&not, private_key),
+            BigUint::from(1 - bit)
+        );
+
+        // NOT is an involution
+        let not_not = public_key.homomorphic_not(&not).unwrap();
+        assert_eq!(
+            OkamotoUchiyama::decrypt(&not_not, private_key),
+            BigUint::from(bit)
+        );
+    }
+
+    // A ciphertext sharing a factor with n is not invertible
+    let p_multiple = Ciphertext::new(private_key.p.clone());
+    assert!(matches!(
+        public_key.homomorphic_not(&p_multiple),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_weighted_average_prep() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // (weight, value) pairs
+    let samples = [(2u64, 10u64), (3, 20), (5, 4)];
+    let weights: Vec<_> = samples
+        .iter()
+        .map(|&(w, _)| OkamotoUchiyama::encrypt(&BigUint::from(w), public_key))
+        .collect();
+    let weighted_values: Vec<_> = samples
+        .iter()
+        .map(|&(w, x)| OkamotoUchiyama::encrypt(&BigUint::from(w * x), public_key))
+        .collect();
+
+    let (denominator, numerator) = public_key
+        .weighted_average_prep(&weights, &weighted_values)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&denominator, private_key),
+        BigUint::from(10u64)
+    );
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&numerator, private_key),
+        BigUint::from(100u64)
+    );
+
+    // Length mismatch
+    assert!(matches!(
+        public_key.weighted_average_prep(&weights, &weighted_values[1..]),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+}
+
+#[test]
+fn test_fluent_chain() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let c1 = OkamotoUchiyama::encrypt(&BigUint::from(12u64), public_key);
+    let c2 = OkamotoUchiyama::encrypt(&BigUint::from(30u64), public_key);
+    let k = BigUint::from(3u64);
+    let j = BigUint::from(5u64);
+
+    // (12 + 30) * 3 + 5 = 131
+    let fluent = c1
+        .with_key(public_key)
+        .add(&c2)
+        .unwrap()
+        .mul_const(&k)
+        .unwrap()
+        .add_const(&j)
+        .unwrap()
+        .finish();
+
+    // Same computation step by step
+    let sum = public_key.homomorphic_encrypt_two(&c1, &c2).unwrap();
+    let product = Ciphertext::new(sum.value().modpow(&k, &public_key.n));
+    let step_by_step = public_key
+        .homomorphic_encrypt_two(&product, &public_key.encrypt_constant(&j))
+        .unwrap();
+
+    assert_eq!(fluent, step_by_step);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&fluent, private_key),
+        BigUint::from(131u64)
+    );
+
+    // Invalid operands stop the chain
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    assert!(matches!(
+        c1.with_key(public_key).add(&zero),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_small_message_encryption() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Small messages match the general modpow
+    for m in 0..256u64 {
+        let m = BigUint::from(m);
+        assert_eq!(
+            public_key.encrypt_constant(&m).value(),
+            &public_key.g.modpow(&m, &public_key.n)
+        );
+    }
+
+    // Messages around the fast path boundary
+    for m in [255u64, 256, u32::MAX as u64 + 1] {
+        let m = BigUint::from(m);
+        assert_eq!(
+            public_key.encrypt_constant(&m).value(),
+            &public_key.g.modpow(&m, &public_key.n)
+        );
+        let c = OkamotoUchiyama::encrypt(&m, public_key);
+        assert_eq!(OkamotoUchiyama::decrypt(&c, private_key), m);
+    }
+}
+
+#[test]
+fn test_homomorphic_affine() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // 2 * 3 + 5 = 11
+    let c = OkamotoUchiyama::encrypt(&BigUint::from(3u64), public_key);
+    let result = public_key
+        .homomorphic_affine(&c, &BigUint::from(2u64), &BigUint::from(5u64))
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&result, private_key),
+        BigUint::from(11u64)
+    );
+
+    // The result wraps around mod p
+    let result = public_key
+        .homomorphic_affine(&c, &BigUint::from(1u64), &(&private_key.p - 1u32))
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&result, private_key),
+        BigUint::from(2u64)
+    );
+}
+
+#[test]
+fn test_bounded_add() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let bound = BigUint::from(1000u64);
+
+    // 700 + 600 exceeds the bound, the client hints a reduction by 1000
+    let c1 = OkamotoUchiyama::encrypt(&BigUint::from(700u64), public_key);
+    let c2 = OkamotoUchiyama::encrypt(&BigUint::from(600u64), public_key);
+    let hint = OkamotoUchiyama::encrypt(&bound, public_key);
+    let result = public_key.bounded_add(&c1, &c2, &hint).unwrap();
+    assert_eq!(
+        private_key
+            .decrypt_with_overflow_check(&result, &bound)
+            .unwrap(),
+        BigUint::from(300u64)
+    );
+
+    // A wrong hint is caught when decrypting against the bound
+    let hint = OkamotoUchiyama::encrypt(&BigUint::from(2000u64), public_key);
+    let result = public_key.bounded_add(&c1, &c2, &hint).unwrap();
+    assert!(matches!(
+        private_key.decrypt_with_overflow_check(&result, &bound),
+        Err(OkamotoUchiyamaError::LikelyWrapped)
+    ));
+
+    // Invalid operands are rejected
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    assert!(matches!(
+        public_key.bounded_add(&c1, &c2, &zero),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_homomorphic_poly_eval() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // 3x^2 + 2x + 5 at x = 4
+    let power_cts = [
+        OkamotoUchiyama::encrypt(&BigUint::from(4u64), public_key),
+        OkamotoUchiyama::encrypt(&BigUint::from(16u64), public_key),
+    ];
+    let coeffs = [5u64, 2, 3].map(BigUint::from);
+    let result = public_key
+        .homomorphic_poly_eval(&power_cts, &coeffs)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&result, private_key),
+        BigUint::from(61u64)
+    );
+
+    // One coefficient is needed per power, plus the constant term
+    assert!(matches!(
+        public_key.homomorphic_poly_eval(&power_cts, &coeffs[1..]),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+    assert!(matches!(
+        public_key.homomorphic_poly_eval(&[], &[]),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+}
+
+#[test]
+fn test_precomputed_encryption_tables() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    for window_bits in [2, 4, 6] {
+        let mut tabled_key = public_key.clone();
+        tabled_key.precompute_encryption_tables(window_bits);
+        assert_eq!(
+            tabled_key.encryption_tables().unwrap().window_bits(),
+            window_bits
+        );
+
+        // Tables are excluded from equality
+        assert_eq!(&tabled_key, public_key);
+
+        // Same ciphertext as the plain exponentiation for a given randomness
+        let m = BigUint::from(123456789u64);
+        let constant = tabled_key.encrypt_constant(&m);
+        assert_eq!(constant, public_key.encrypt_constant(&m));
+
+        // Table-based ciphertexts decrypt correctly
+        for m in [0u64, 1, 255, 256, 1337, u64::MAX] {
+            let m = BigUint::from(m);
+            let c = OkamotoUchiyama::encrypt(&m, &tabled_key);
+            assert_eq!(OkamotoUchiyama::decrypt(&c, private_key), m);
+        }
+
+        // Messages larger than the table fall back to modpow
+        let large = &public_key.n * 3u32;
+        assert_eq!(
+            tabled_key.encrypt_constant(&large),
+            public_key.encrypt_constant(&large)
+        );
+    }
+
+    // Tables built for a previous g are ignored
+    let mut tabled_key = public_key.clone();
+    tabled_key.precompute_encryption_tables(4);
+    tabled_key.g = &public_key.g + 1u32;
+    assert!(tabled_key.encryption_tables().is_none());
+    let m = BigUint::from(1337u64);
+    assert_eq!(
+        tabled_key.encrypt_constant(&m),
+        PublicKey::new(&tabled_key.n, &tabled_key.g, &tabled_key.h).encrypt_constant(&m)
+    );
+}
+
+#[cfg(feature = "subtle")]
+#[test]
+fn test_conditional_select() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let a = OkamotoUchiyama::encrypt(&BigUint::from(1u64), public_key);
+    let b = OkamotoUchiyama::encrypt(&BigUint::from(2u64), public_key);
+
+    let selected = Ciphertext::conditional_select(&a, &b, subtle::Choice::from(0));
+    assert_eq!(selected, a);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&selected, private_key),
+        BigUint::from(1u64)
+    );
+
+    let selected = Ciphertext::conditional_select(&a, &b, subtle::Choice::from(1));
+    assert_eq!(selected, b);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&selected, private_key),
+        BigUint::from(2u64)
+    );
+
+    // Values of different byte lengths
+    let small = Ciphertext::new(BigUint::from(7u64));
+    assert_eq!(
+        Ciphertext::conditional_select(&a, &small, subtle::Choice::from(1)),
+        small
+    );
+}
+
+#[cfg(feature = "subtle")]
+#[test]
+fn test_homomorphic_add_conditional() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Oblivious sum of the selected terms
+    let terms = [(10u64, 1u8), (20, 0), (30, 1), (40, 0)];
+    let mut acc = OkamotoUchiyama::encrypt(&BigUint::from(0u64), public_key);
+    for (term, include) in terms {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(term), public_key);
+        let next = public_key
+            .homomorphic_add_conditional(&acc, &c, subtle::Choice::from(include))
+            .unwrap();
+
+        // Excluding a term still changes the accumulator, and including it does not give
+        // the plain product acc * term
+        assert_ne!(next, acc);
+        assert_ne!(next, public_key.homomorphic_encrypt_two(&acc, &c).unwrap());
+        acc = next;
+    }
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&acc, private_key),
+        BigUint::from(40u64)
+    );
+
+    let invalid = Ciphertext::new(public_key.n.clone());
+    for include in [0u8, 1] {
+        assert!(public_key
+            .homomorphic_add_conditional(&acc, &invalid, subtle::Choice::from(include))
+            .is_err());
+    }
+}
+
+#[test]
+fn test_encrypt_lines_round_trip() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Blank lines and surrounding whitespace are ignored
+    let input = "42\n\n  1337 \n0\n\n123456789\n";
+    let mut encrypted = Vec::new();
+    let count =
+        OkamotoUchiyama::encrypt_lines(input.as_bytes(), public_key, &mut encrypted).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(
+        String::from_utf8(encrypted.clone())
+            .unwrap()
+            .lines()
+            .count(),
+        4
+    );
+
+    let mut decrypted = Vec::new();
+    let count =
+        OkamotoUchiyama::decrypt_lines(encrypted.as_slice(), private_key, &mut decrypted).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(
+        String::from_utf8(decrypted).unwrap(),
+        "42\n1337\n0\n123456789\n"
+    );
+
+    // Parse errors report the 1-based line number, blank lines included
+    assert!(matches!(
+        OkamotoUchiyama::encrypt_lines("1\n\nabc\n".as_bytes(), public_key, Vec::new()),
+        Err(OkamotoUchiyamaError::InvalidLine(3))
+    ));
+    assert!(matches!(
+        OkamotoUchiyama::encrypt_lines("-5\n".as_bytes(), public_key, Vec::new()),
+        Err(OkamotoUchiyamaError::InvalidLine(1))
+    ));
+
+    // Values that are not ciphertexts for the key are rejected
+    let invalid = format!("{}\n", public_key.n);
+    assert!(matches!(
+        OkamotoUchiyama::decrypt_lines(invalid.as_bytes(), private_key, Vec::new()),
+        Err(OkamotoUchiyamaError::InvalidLine(1))
+    ));
+}
+
+#[test]
+fn test_encrypt_with_policy() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let p = &private_key.p;
+
+    // An oversized message, and one below n but not below p
+    let oversized = &public_key.n * 3u32 + p * 2u32 + 7u32;
+    let wrapping = p + 7u32;
+
+    assert!(matches!(
+        OkamotoUchiyama::encrypt_with_policy(&oversized, public_key, EncryptPolicy::Strict),
+        Err(OkamotoUchiyamaError::MessageTooLarge)
+    ));
+    let c =
+        OkamotoUchiyama::encrypt_with_policy(&wrapping, public_key, EncryptPolicy::Strict).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&c, private_key),
+        BigUint::from(7u32)
+    );
+
+    for policy in [
+        EncryptPolicy::ReduceModN,
+        EncryptPolicy::ReduceModP(private_key),
+    ] {
+        let c = OkamotoUchiyama::encrypt_with_policy(&oversized, public_key, policy).unwrap();
+        assert_eq!(OkamotoUchiyama::decrypt(&c, private_key), &oversized % p);
+    }
+
+    // Reducing with the private key of another public key fails
+    let other_private_key = common::small_private_key();
+    assert!(matches!(
+        OkamotoUchiyama::encrypt_with_policy(
+            &oversized,
+            public_key,
+            EncryptPolicy::ReduceModP(&other_private_key)
+        ),
+        Err(OkamotoUchiyamaError::KeyMismatch)
+    ));
+}
+
+#[test]
+fn test_encrypt_with_rng() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let message = BigUint::from(1337u64);
+
+    // The same seed yields the same ciphertext
+    let c1 =
+        OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut StdRng::seed_from_u64(42));
+    let c2 =
+        OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut StdRng::seed_from_u64(42));
+    assert_eq!(c1, c2);
+    assert_eq!(OkamotoUchiyama::decrypt(&c1, private_key), message);
+
+    // Another seed, or the next draw of the same RNG, yields another ciphertext
+    let c3 =
+        OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut StdRng::seed_from_u64(43));
+    assert_ne!(c1, c3);
+    let mut rng = StdRng::seed_from_u64(42);
+    let first = OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut rng);
+    let second = OkamotoUchiyama::encrypt_with_rng(&message, public_key, &mut rng);
+    assert_eq!(first, c1);
+    assert_ne!(first, second);
+
+    // r stays in [1, n - 1) even for a tiny modulus
+    let small_key = common::small_private_key();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let c = OkamotoUchiyama::encrypt_with_rng(&message, &small_key.public_key, &mut rng);
+        assert!(small_key.public_key.accepts(&c));
+        assert_eq!(
+            OkamotoUchiyama::decrypt(&c, &small_key),
+            &message % &small_key.p
+        );
+    }
+}
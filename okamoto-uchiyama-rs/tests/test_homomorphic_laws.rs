@@ -0,0 +1,156 @@
+mod common;
+
+use num_bigint_dig::{BigUint, RandBigInt};
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// Private keys to check the laws with: the shared key and random keys built from a prime pool
+fn keys(rng: &mut StdRng) -> Vec<PrivateKey> {
+    let pool: Vec<BigUint> = [1999u64, 2003, 2011, 2017, 2027, 2029, 2039, 2053]
+        .iter()
+        .map(|&p| BigUint::from(p))
+        .collect();
+
+    let mut keys = vec![common::shared_private_key().clone()];
+    for _ in 0..4 {
+        let okamoto_uchiyama = OkamotoUchiyama::init_from_prime_pool(&pool, &pool, rng).unwrap();
+        keys.push(okamoto_uchiyama.generate_private_key().unwrap());
+    }
+    keys
+}
+
+// Encrypts three random messages below p
+fn random_ciphertexts(private_key: &PrivateKey, rng: &mut StdRng) -> [Ciphertext; 3] {
+    [0, 1, 2].map(|_| {
+        let message = rng.gen_biguint_below(&private_key.p);
+        OkamotoUchiyama::encrypt(&message, &private_key.public_key)
+    })
+}
+
+#[test]
+fn test_homomorphic_addition_is_commutative() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for private_key in keys(&mut rng) {
+        let public_key = &private_key.public_key;
+        for _ in 0..5 {
+            let [a, b, _] = random_ciphertexts(&private_key, &mut rng);
+
+            let ab = public_key.homomorphic_encrypt_two(&a, &b).unwrap();
+            let ba = public_key.homomorphic_encrypt_two(&b, &a).unwrap();
+            assert_eq!(
+                OkamotoUchiyama::decrypt(&ab, &private_key),
+                OkamotoUchiyama::decrypt(&ba, &private_key)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_homomorphic_addition_is_associative() {
+    let mut rng = StdRng::seed_from_u64(2);
+
+    for private_key in keys(&mut rng) {
+        let public_key = &private_key.public_key;
+        for _ in 0..5 {
+            let [a, b, c] = random_ciphertexts(&private_key, &mut rng);
+
+            let ab = public_key.homomorphic_encrypt_two(&a, &b).unwrap();
+            let ab_c = public_key.homomorphic_encrypt_two(&ab, &c).unwrap();
+            let bc = public_key.homomorphic_encrypt_two(&b, &c).unwrap();
+            let a_bc = public_key.homomorphic_encrypt_two(&a, &bc).unwrap();
+            assert_eq!(
+                OkamotoUchiyama::decrypt(&ab_c, &private_key),
+                OkamotoUchiyama::decrypt(&a_bc, &private_key)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_homomorphic_multiple_matches_pairwise_additions() {
+    let mut rng = StdRng::seed_from_u64(3);
+
+    for private_key in keys(&mut rng) {
+        let public_key = &private_key.public_key;
+        for _ in 0..5 {
+            let [a, b, c] = random_ciphertexts(&private_key, &mut rng);
+
+            let multiple = public_key
+                .homomorphic_encrypt_multiple(vec![&a, &b, &c])
+                .unwrap();
+            let ab = public_key.homomorphic_encrypt_two(&a, &b).unwrap();
+            let ab_c = public_key.homomorphic_encrypt_two(&ab, &c).unwrap();
+            assert_eq!(
+                OkamotoUchiyama::decrypt(&multiple, &private_key),
+                OkamotoUchiyama::decrypt(&ab_c, &private_key)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_homomorphic_multiple_equals_fold_of_two_exactly() {
+    let mut rng = StdRng::seed_from_u64(4);
+
+    for private_key in keys(&mut rng) {
+        let public_key = &private_key.public_key;
+        for len in 1..=6 {
+            let ciphertexts: Vec<Ciphertext> = (0..len)
+                .map(|_| {
+                    let message = rng.gen_biguint_below(&private_key.p);
+                    OkamotoUchiyama::encrypt(&message, public_key)
+                })
+                .collect();
+
+            // Reducing once at the end or after every product gives the same value mod n
+            let multiple = public_key
+                .homomorphic_encrypt_multiple(ciphertexts.iter().collect())
+                .unwrap();
+            let folded = ciphertexts[1..]
+                .iter()
+                .fold(ciphertexts[0].clone(), |acc, c| {
+                    public_key.homomorphic_encrypt_two(&acc, c).unwrap()
+                });
+            assert_eq!(multiple.value(), folded.value());
+            assert_eq!(multiple, folded);
+        }
+    }
+}
+
+#[test]
+fn test_sliding_window_sum_matches_naive_window_sums() {
+    let mut rng = StdRng::seed_from_u64(754);
+
+    for private_key in keys(&mut rng) {
+        let public_key = &private_key.public_key;
+        let ciphertexts: Vec<Ciphertext> = (0..12)
+            .map(|_| {
+                let message = rng.gen_biguint_below(&private_key.p);
+                OkamotoUchiyama::encrypt(&message, public_key)
+            })
+            .collect();
+
+        for window in 1..=ciphertexts.len() {
+            let sums = public_key.sliding_window_sum(&ciphertexts, window).unwrap();
+            assert_eq!(sums.len(), ciphertexts.len() - window + 1);
+            for (sum, naive_window) in sums.iter().zip(ciphertexts.windows(window)) {
+                let naive = public_key.homomorphic_sum_of(naive_window).unwrap();
+                assert_eq!(sum, &naive);
+                assert_eq!(
+                    OkamotoUchiyama::decrypt(sum, &private_key),
+                    OkamotoUchiyama::decrypt(&naive, &private_key)
+                );
+            }
+        }
+
+        // The window must hold between 1 and all of the ciphertexts
+        assert!(public_key.sliding_window_sum(&ciphertexts, 0).is_err());
+        assert!(public_key
+            .sliding_window_sum(&ciphertexts, ciphertexts.len() + 1)
+            .is_err());
+        assert!(public_key.sliding_window_sum(&[], 1).is_err());
+    }
+}
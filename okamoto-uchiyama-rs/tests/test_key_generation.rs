@@ -0,0 +1,315 @@
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::provenance::PrimeSource;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::key::{KeyGenOptions, KeySize, MILLER_RABIN_ROUNDS};
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey};
+use rand::rngs::{StdRng, ThreadRng};
+use rand::SeedableRng;
+use std::any::type_name;
+
+#[test]
+fn test_generate_keys_from_uninitialized_struct() {
+    // A default instance has not been initialized with `init`
+    let okamoto_uchiyama = OkamotoUchiyama::default();
+
+    assert!(matches!(
+        okamoto_uchiyama.generate_private_key(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+    assert!(matches!(
+        okamoto_uchiyama.generate_public_key(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_prime_sizes() {
+    assert_eq!(KeySize::Bits1024.prime_sizes().unwrap(), (341, 512));
+    assert_eq!(KeySize::Custom(96).prime_sizes().unwrap(), (32, 48));
+}
+
+#[test]
+fn test_too_small_custom_key_size() {
+    // 32 / 3 = 10 bits for p, below the minimum prime size
+    assert!(matches!(
+        KeySize::Custom(32).prime_sizes(),
+        Err(OkamotoUchiyamaError::InvalidKeySize)
+    ));
+    assert!(matches!(
+        OkamotoUchiyama::init(KeySize::Custom(32)),
+        Err(OkamotoUchiyamaError::InvalidKeySize)
+    ));
+    assert!(matches!(
+        OkamotoUchiyama::init(KeySize::Custom(0)),
+        Err(OkamotoUchiyamaError::InvalidKeySize)
+    ));
+}
+
+#[test]
+fn test_primality_confidence() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    assert_eq!(
+        okamoto_uchiyama.provenance().primality_rounds,
+        MILLER_RABIN_ROUNDS
+    );
+
+    // 2 * 4^(-8) = 2^(-15)
+    let expected = 2.0 * 4f64.powi(-(MILLER_RABIN_ROUNDS as i32));
+    assert_eq!(okamoto_uchiyama.primality_confidence(), expected);
+    assert_eq!(okamoto_uchiyama.primality_confidence(), 1.0 / 32768.0);
+
+    // More rounds give a lower false positive probability
+    let mut more_rounds = okamoto_uchiyama.clone();
+    more_rounds.provenance.primality_rounds = 16;
+    assert!(more_rounds.primality_confidence() < expected);
+}
+
+#[test]
+fn test_init_with_ratio() {
+    // p sized at a quarter of n, q at the remaining half
+    let okamoto_uchiyama = OkamotoUchiyama::init_with_ratio(1024, 0.25).unwrap();
+    assert!(okamoto_uchiyama.p.bits() <= 256);
+    assert!(okamoto_uchiyama.q.bits() <= 512);
+
+    // n = p^2 * q fills the requested size, up to the leading bits of the primes
+    let n_bits = okamoto_uchiyama.n.bits();
+    assert!(n_bits <= 1024);
+    assert!(n_bits > 1024 - 16);
+    assert_eq!(okamoto_uchiyama.length, 1024);
+
+    // Round-trip encryption
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let message = BigUint::from(1337u64);
+    let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+
+    // Fractions leaving no room for q are rejected
+    for (total_bits, p_fraction) in [(1024, 0.5), (1024, 0.0), (1024, f64::NAN)] {
+        assert!(matches!(
+            OkamotoUchiyama::init_with_ratio(total_bits, p_fraction),
+            Err(OkamotoUchiyamaError::InvalidKeySize)
+        ));
+    }
+
+    // Lopsided or too small splits are rejected as insecure
+    for (total_bits, p_fraction) in [
+        (768, 0.03),
+        (2048, 0.03),
+        (2048, 0.45),
+        (768, 0.25),
+        (64, 0.1),
+    ] {
+        assert!(matches!(
+            OkamotoUchiyama::init_with_ratio(total_bits, p_fraction),
+            Err(OkamotoUchiyamaError::InsecureKeySize(_))
+        ));
+    }
+}
+
+#[test]
+fn test_init_from_prime_pool() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let pool: Vec<BigUint> = [1999u64, 2003, 2011, 2017, 2027]
+        .iter()
+        .map(|&p| BigUint::from(p))
+        .collect();
+
+    let okamoto_uchiyama = OkamotoUchiyama::init_from_prime_pool(&pool, &pool, &mut rng).unwrap();
+    assert!(pool.contains(&okamoto_uchiyama.p));
+    assert!(pool.contains(&okamoto_uchiyama.q));
+    assert_ne!(okamoto_uchiyama.p, okamoto_uchiyama.q);
+
+    // Round-trip encryption with the selected primes
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let message = BigUint::from(1337u64);
+    let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+
+    // The same seed selects the same key
+    let mut rng = StdRng::seed_from_u64(7);
+    let again = OkamotoUchiyama::init_from_prime_pool(&pool, &pool, &mut rng).unwrap();
+    assert_eq!(
+        again.export_parameters(),
+        okamoto_uchiyama.export_parameters()
+    );
+
+    // Composite candidates, 0, 1 and pools without distinct primes are rejected
+    for (p_pool, q_pool) in [
+        (vec![BigUint::from(2001u64)], pool.clone()),
+        (vec![BigUint::from(0u64)], pool.clone()),
+        (vec![BigUint::from(1u64)], vec![BigUint::from(0u64)]),
+        (pool.clone(), vec![BigUint::from(1u64)]),
+        (vec![BigUint::from(2003u64)], vec![BigUint::from(2003u64)]),
+        (vec![], pool.clone()),
+    ] {
+        assert!(matches!(
+            OkamotoUchiyama::init_from_prime_pool(&p_pool, &q_pool, &mut rng),
+            Err(OkamotoUchiyamaError::InvalidKey)
+        ));
+    }
+}
+
+#[test]
+fn test_provenance() {
+    // Freshly generated primes
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    let provenance = okamoto_uchiyama.provenance();
+    assert_eq!(provenance.prime_source, PrimeSource::NumPrimes);
+    assert_eq!(provenance.rng, Some(type_name::<ThreadRng>()));
+    assert_eq!(provenance.primality_rounds, MILLER_RABIN_ROUNDS);
+
+    // Primes from a pool, with the caller's RNG
+    let pool = [BigUint::from(2003u64), BigUint::from(2351u64)];
+    let mut rng = StdRng::seed_from_u64(7);
+    let pooled = OkamotoUchiyama::init_from_prime_pool(&pool, &pool, &mut rng).unwrap();
+    assert_eq!(pooled.provenance().prime_source, PrimeSource::Pool);
+    assert_eq!(pooled.provenance().rng, Some(type_name::<StdRng>()));
+
+    // Imported parameters
+    let imported = OkamotoUchiyama::from_parameters(&pooled.export_parameters()).unwrap();
+    assert_eq!(imported.provenance().prime_source, PrimeSource::Imported);
+    assert_eq!(imported.provenance().rng, None);
+
+    assert_eq!(
+        OkamotoUchiyama::default().provenance().prime_source,
+        PrimeSource::Unknown
+    );
+}
+
+#[test]
+fn test_init_for_ciphertext_bytes() {
+    for target_bytes in [32, 64, 65] {
+        let okamoto_uchiyama = OkamotoUchiyama::init_for_ciphertext_bytes(target_bytes).unwrap();
+        let public_key = okamoto_uchiyama.generate_public_key().unwrap();
+        assert_eq!(public_key.ciphertext_byte_len(), target_bytes);
+
+        let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(1337u64), &public_key);
+        assert!(ciphertext.value().to_bytes_be().len() <= target_bytes);
+    }
+
+    // Too small for the primes
+    assert!(matches!(
+        OkamotoUchiyama::init_for_ciphertext_bytes(4),
+        Err(OkamotoUchiyamaError::InvalidKeySize)
+    ));
+}
+
+#[test]
+fn test_derive_subkey() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    let master = okamoto_uchiyama.generate_public_key().unwrap();
+
+    let alice = okamoto_uchiyama.derive_subkey(b"tenant-alice").unwrap();
+    let bob = okamoto_uchiyama.derive_subkey(b"tenant-bob").unwrap();
+
+    // Same modulus, distinct generators
+    assert_eq!(alice.n, master.n);
+    assert_eq!(bob.n, master.n);
+    assert_ne!(alice.g, master.g);
+    assert_ne!(alice.g, bob.g);
+    assert_ne!(alice.h, bob.h);
+
+    // Deterministic per context
+    assert_eq!(
+        okamoto_uchiyama.derive_subkey(b"tenant-alice").unwrap(),
+        alice
+    );
+
+    // Each subkey decrypts with the master p and q
+    let message = BigUint::from(1337u64);
+    for subkey in [alice, bob] {
+        let private_key = PrivateKey::new(&subkey, &okamoto_uchiyama.p, &okamoto_uchiyama.q);
+        assert!(private_key.validate().is_ok());
+        let ciphertext = OkamotoUchiyama::encrypt(&message, &subkey);
+        assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+    }
+
+    assert!(matches!(
+        OkamotoUchiyama::default().derive_subkey(b"tenant-alice"),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_compute_h() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::compute_h(&okamoto_uchiyama.g, &okamoto_uchiyama.n),
+        okamoto_uchiyama.h
+    );
+
+    let public_key = okamoto_uchiyama.generate_public_key().unwrap();
+    assert_eq!(
+        OkamotoUchiyama::compute_h(&public_key.g, &public_key.n),
+        public_key.h
+    );
+
+    let subkey = okamoto_uchiyama.derive_subkey(b"context").unwrap();
+    assert_eq!(OkamotoUchiyama::compute_h(&subkey.g, &subkey.n), subkey.h);
+
+    assert_eq!(
+        OkamotoUchiyama::compute_h(&BigUint::from(2u32), &BigUint::from(0u32)),
+        BigUint::from(0u32)
+    );
+}
+
+#[test]
+fn test_validate_security() {
+    // Balanced splits of common sizes pass
+    for (p_bits, q_bits) in [(342, 342), (341, 512), (683, 682), (256, 512)] {
+        assert!(KeyGenOptions::new(p_bits, q_bits)
+            .validate_security()
+            .is_ok());
+    }
+
+    // A small modulus, a small prime or an unbalanced split fail
+    for (p_bits, q_bits) in [(170, 256), (128, 1024), (600, 256), (256, 700)] {
+        assert!(matches!(
+            KeyGenOptions::new(p_bits, q_bits).validate_security(),
+            Err(OkamotoUchiyamaError::InsecureKeySize(_))
+        ));
+    }
+
+    // The reason names the failed requirement
+    let error = KeyGenOptions::new(128, 1024)
+        .validate_security()
+        .unwrap_err();
+    assert!(error.to_string().contains("p has 128 bits"));
+
+    // Insecure options are rejected before any prime is generated
+    assert!(matches!(
+        OkamotoUchiyama::init_with_options(&KeyGenOptions::new(16, 16)),
+        Err(OkamotoUchiyamaError::InsecureKeySize(_))
+    ));
+}
+
+#[test]
+fn test_init_with_derived_g() {
+    let okamoto_uchiyama = OkamotoUchiyama::init_with_derived_g(KeySize::Bits512).unwrap();
+    let (n, p) = (&okamoto_uchiyama.n, &okamoto_uchiyama.p);
+
+    // g is reproducible from n, and its counter lets anyone check it
+    let (g, counter) = OkamotoUchiyama::derive_g(n, p);
+    assert_eq!(okamoto_uchiyama.g, g);
+    assert_eq!(OkamotoUchiyama::derive_g(n, p), (g.clone(), counter));
+    assert_eq!(OkamotoUchiyama::g_candidate(n, counter), g);
+    assert!(g >= BigUint::from(2u32) && &g < n);
+    assert_eq!(okamoto_uchiyama.h, OkamotoUchiyama::compute_h(&g, n));
+    assert_eq!(
+        okamoto_uchiyama.gpminuse1,
+        g.modpow(&(p - 1u32), &okamoto_uchiyama.p_squared)
+    );
+    assert_eq!(okamoto_uchiyama.provenance().rng, None);
+
+    // Other counters give other candidates
+    assert_ne!(
+        OkamotoUchiyama::g_candidate(n, counter + 1),
+        OkamotoUchiyama::g_candidate(n, counter)
+    );
+
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    assert!(private_key.validate().is_ok());
+    let message = BigUint::from(1337u32);
+    let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+}
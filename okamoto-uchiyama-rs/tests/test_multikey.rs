@@ -0,0 +1,57 @@
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::multikey::combine_plaintexts_crt;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_combine_plaintexts_crt() {
+    let p1 = BigUint::from(1999u64);
+    let p2 = BigUint::from(2003u64);
+    let value = BigUint::from(3_141_592u64);
+
+    let x =
+        combine_plaintexts_crt(&[(&value % &p1, p1.clone()), (&value % &p2, p2.clone())]).unwrap();
+    assert_eq!(x, value);
+
+    // Residues are reduced first and the result is below the product of the moduli
+    let x = combine_plaintexts_crt(&[(&value + &p1, p1.clone())]).unwrap();
+    assert_eq!(x, &value % &p1);
+    assert_eq!(combine_plaintexts_crt(&[]).unwrap(), BigUint::from(0u32));
+
+    assert!(matches!(
+        combine_plaintexts_crt(&[(BigUint::from(1u32), p1.clone()), (BigUint::from(2u32), p1)]),
+        Err(OkamotoUchiyamaError::ModuliNotCoprime)
+    ));
+    assert!(matches!(
+        combine_plaintexts_crt(&[(BigUint::from(1u32), BigUint::from(0u32))]),
+        Err(OkamotoUchiyamaError::InvalidDivisor)
+    ));
+}
+
+#[test]
+fn test_combine_plaintexts_decrypted_under_two_keys() {
+    let pool = |primes: &[u64]| primes.iter().map(|&p| BigUint::from(p)).collect::<Vec<_>>();
+    let mut rng = rand::thread_rng();
+    let first = OkamotoUchiyama::init_from_prime_pool(&pool(&[1999]), &pool(&[2027]), &mut rng)
+        .unwrap()
+        .generate_private_key()
+        .unwrap();
+    let second = OkamotoUchiyama::init_from_prime_pool(&pool(&[2003]), &pool(&[2029]), &mut rng)
+        .unwrap()
+        .generate_private_key()
+        .unwrap();
+
+    // Each party encrypts the same total under its own key, which wraps mod its prime
+    let total = BigUint::from(1_000_000u64);
+    let results: Vec<(BigUint, BigUint)> = [&first, &second]
+        .iter()
+        .map(|private_key| {
+            let c = OkamotoUchiyama::encrypt(&total, &private_key.public_key);
+            (
+                OkamotoUchiyama::decrypt(&c, private_key),
+                private_key.p.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(combine_plaintexts_crt(&results).unwrap(), total);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::parameters::ParameterSet;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::key::KeySize;
+use okamoto_uchiyama::pem::PemEncodable;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_export_and_import_parameters() {
+    let message = BigUint::from(1337u64);
+
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    let parameters = okamoto_uchiyama.export_parameters();
+
+    // Rebuild the cryptosystem from the exported parameters
+    let rebuilt = OkamotoUchiyama::from_parameters(&parameters).unwrap();
+    assert_eq!(rebuilt.export_parameters(), parameters);
+
+    let private_key = okamoto_uchiyama.generate_private_key().unwrap();
+    let rebuilt_private_key = rebuilt.generate_private_key().unwrap();
+    assert_eq!(rebuilt_private_key, private_key);
+
+    // Ciphertexts are interchangeable between both instances
+    let ciphertext = OkamotoUchiyama::encrypt(&message, &rebuilt_private_key.public_key);
+    assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+}
+
+#[test]
+fn test_import_invalid_parameters() {
+    let valid = ParameterSet {
+        p: BigUint::from(2003u64),
+        q: BigUint::from(2351u64),
+        g: BigUint::from(8083706871u64),
+        length: 34,
+    };
+    assert!(OkamotoUchiyama::from_parameters(&valid).is_ok());
+
+    let invalid = [
+        // p is not prime
+        ParameterSet {
+            p: BigUint::from(2001u64),
+            ..valid.clone()
+        },
+        // p == q
+        ParameterSet {
+            q: BigUint::from(2003u64),
+            ..valid.clone()
+        },
+        // g is out of range
+        ParameterSet {
+            g: BigUint::from(9432233159u64),
+            ..valid.clone()
+        },
+        // g^(p-1) mod p^2 == 1
+        ParameterSet {
+            g: BigUint::from(1u64),
+            ..valid.clone()
+        },
+        // length is not the bit length of n
+        ParameterSet {
+            length: 35,
+            ..valid.clone()
+        },
+        ParameterSet {
+            length: 33,
+            ..valid.clone()
+        },
+    ];
+
+    for parameters in invalid {
+        assert!(matches!(
+            OkamotoUchiyama::from_parameters(&parameters),
+            Err(OkamotoUchiyamaError::InvalidKey)
+        ));
+    }
+}
+
+#[test]
+fn test_parameters_der_and_pem_round_trip() {
+    let okamoto_uchiyama = OkamotoUchiyama::init(KeySize::Bits512).unwrap();
+    let parameters = okamoto_uchiyama.export_parameters();
+    assert_eq!(parameters.length as usize, okamoto_uchiyama.n.bits());
+
+    // DER
+    let decoded = ParameterSet::from_der(&parameters.to_der()).unwrap();
+    assert_eq!(decoded, parameters);
+
+    // PEM
+    let pem = parameters.to_pem();
+    assert!(pem.starts_with("-----BEGIN OKAMOTO-UCHIYAMA PARAMETERS-----\n"));
+    let decoded = ParameterSet::from_pem(&pem).unwrap();
+    assert_eq!(decoded, parameters);
+
+    // The decoded parameters rebuild the same cryptosystem
+    let rebuilt = OkamotoUchiyama::from_parameters(&decoded).unwrap();
+    assert_eq!(
+        rebuilt.generate_private_key().unwrap(),
+        okamoto_uchiyama.generate_private_key().unwrap()
+    );
+
+    // Truncated DER, a SEQUENCE of 3 INTEGERs and a mismatched label are rejected
+    let der = parameters.to_der();
+    assert!(matches!(
+        ParameterSet::from_der(&der[..der.len() - 1]),
+        Err(OkamotoUchiyamaError::DecodingError)
+    ));
+    let public_key_der = okamoto_uchiyama
+        .generate_private_key()
+        .unwrap()
+        .public_key
+        .to_der();
+    assert!(matches!(
+        ParameterSet::from_der(&public_key_der),
+        Err(OkamotoUchiyamaError::ElementCountMismatch {
+            expected: 4,
+            found: 3
+        })
+    ));
+    assert!(matches!(
+        ParameterSet::from_pem(&pem.replace("PARAMETERS", "PUBLIC KEY")),
+        Err(OkamotoUchiyamaError::PemDecodingError(None))
+    ));
+}
@@ -0,0 +1,454 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::private_key::DecryptionStrategy;
+use okamoto_uchiyama::crypto::signed_ciphertext::SignedCiphertext;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::pem::PemEncodable;
+use okamoto_uchiyama::{OkamotoUchiyama, PrivateKey, PublicKey};
+use std::collections::HashSet;
+
+#[test]
+fn test_is_quadratic_residue() {
+    let private_key = common::small_private_key();
+
+    // Squares are always quadratic residues
+    for x in [1u64, 4, 9, 16, 1337 * 1337] {
+        assert!(private_key.is_quadratic_residue(&BigUint::from(x)));
+    }
+
+    // p = 2003 is 3 mod 8, so 2 and -1 (= p - 1) are non-residues
+    assert!(!private_key.is_quadratic_residue(&BigUint::from(2u64)));
+    assert!(!private_key.is_quadratic_residue(&BigUint::from(2002u64)));
+
+    // Multiples of p are not residues
+    assert!(!private_key.is_quadratic_residue(&BigUint::from(2003u64)));
+}
+
+#[test]
+fn test_subgroup_orders() {
+    let private_key = common::small_private_key();
+
+    // The plaintext subgroup has order p
+    assert_eq!(
+        private_key.message_subgroup_order(),
+        &BigUint::from(2003u64)
+    );
+
+    // lcm(2002, 2350) = 2352350
+    let randomness_order = private_key.randomness_subgroup_order();
+    assert_eq!(randomness_order, BigUint::from(2352350u64));
+
+    // h raised to the randomness subgroup order is 1 mod n
+    let public_key = &private_key.public_key;
+    assert_eq!(
+        public_key.h.modpow(&randomness_order, &public_key.n),
+        BigUint::from(1u64)
+    );
+}
+
+#[test]
+fn test_decrypt_difference() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let m1 = BigUint::from(1337u64);
+    let m2 = BigUint::from(42u64);
+    let c1 = OkamotoUchiyama::encrypt(&m1, public_key);
+    let c2 = OkamotoUchiyama::encrypt(&m2, public_key);
+
+    // Same result as decrypting separately and subtracting
+    let expected =
+        OkamotoUchiyama::decrypt(&c1, private_key) - OkamotoUchiyama::decrypt(&c2, private_key);
+    assert_eq!(private_key.decrypt_difference(&c1, &c2).unwrap(), expected);
+
+    // A negative difference wraps around modulo p
+    let wrapped = private_key.decrypt_difference(&c2, &c1).unwrap();
+    assert_eq!(wrapped, &private_key.p - (&m1 - &m2));
+
+    // Invalid ciphertexts are rejected
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    assert!(matches!(
+        private_key.decrypt_difference(&c1, &zero),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_pow_mod_wrappers() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let base = BigUint::from(123456789u64);
+    let exp = &private_key.p - 1u32;
+
+    assert_eq!(
+        public_key.pow_mod_n(&base, &exp),
+        base.modpow(&exp, &public_key.n)
+    );
+    assert_eq!(
+        private_key.pow_mod_p_squared(&base, &exp),
+        base.modpow(&exp, &(&private_key.p * &private_key.p))
+    );
+
+    // gd is g^(p-1) mod p^2
+    assert_eq!(
+        private_key.pow_mod_p_squared(&public_key.g, &exp),
+        private_key.gd
+    );
+}
+
+#[test]
+fn test_auto_tune() {
+    let mut private_key = common::shared_private_key().clone();
+    private_key.auto_tune();
+
+    // Decryption stays correct with the selected strategy
+    for m in [0u64, 1, 42, 1337] {
+        let message = BigUint::from(m);
+        let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+        assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+    }
+
+    // Every strategy decrypts correctly
+    for strategy in [
+        DecryptionStrategy::Standard,
+        DecryptionStrategy::ReduceFirst,
+    ] {
+        private_key.set_decryption_strategy(strategy);
+        let message = BigUint::from(4242u64);
+        let ciphertext = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+        assert_eq!(OkamotoUchiyama::decrypt(&ciphertext, &private_key), message);
+    }
+
+    // The strategy is not part of the key identity, so a tuned key still equals its
+    // PEM round-trip
+    private_key.set_decryption_strategy(DecryptionStrategy::ReduceFirst);
+    let decoded = PrivateKey::from_pem(&private_key.to_pem()).unwrap();
+    assert_eq!(decoded.decryption_strategy(), DecryptionStrategy::Standard);
+    assert_eq!(decoded, private_key);
+    assert!(decoded.diff(&private_key).is_empty());
+    assert!(HashSet::from([decoded]).contains(&private_key));
+}
+
+#[test]
+fn test_verify_g() {
+    let private_key = common::small_private_key();
+    assert!(private_key.verify_g());
+    assert!(private_key.validate().is_ok());
+
+    // g = p^2 + 1 satisfies g^(p-1) mod p^2 = 1
+    let p_squared = &private_key.p * &private_key.p;
+    let bad_g = &p_squared + 1u32;
+    let public_key = PublicKey::new(
+        &private_key.public_key.n,
+        &bad_g,
+        &bad_g.modpow(&private_key.public_key.n, &private_key.public_key.n),
+    );
+    let bad_private_key = PrivateKey::new(&public_key, &private_key.p, &private_key.q);
+
+    assert!(!bad_private_key.verify_g());
+    assert!(matches!(
+        bad_private_key.validate(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+
+    // An inconsistent modulus is rejected as well
+    let mut inconsistent = private_key.clone();
+    inconsistent.q = BigUint::from(2347u64);
+    assert!(matches!(
+        inconsistent.validate(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_decrypt_with_overflow_check() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let bound = BigUint::from(1_000_000u64);
+
+    let c1 = OkamotoUchiyama::encrypt(&BigUint::from(500u64), public_key);
+    let c2 = OkamotoUchiyama::encrypt(&BigUint::from(700u64), public_key);
+
+    // 700 - 500 stays in range
+    let difference = SignedCiphertext::from(c2.clone())
+        .homomorphic_subtract(&SignedCiphertext::from(c1.clone()), public_key)
+        .unwrap();
+    assert_eq!(
+        private_key
+            .decrypt_with_overflow_check(difference.ciphertext(), &bound)
+            .unwrap(),
+        BigUint::from(200u64)
+    );
+
+    // 500 - 700 wraps around to p - 200
+    let wrapped = SignedCiphertext::from(c1)
+        .homomorphic_subtract(&SignedCiphertext::from(c2), public_key)
+        .unwrap();
+    assert!(matches!(
+        private_key.decrypt_with_overflow_check(wrapped.ciphertext(), &bound),
+        Err(OkamotoUchiyamaError::LikelyWrapped)
+    ));
+
+    // A multiple of p is rejected instead of panicking in the decryption
+    assert!(matches!(
+        private_key.decrypt_with_overflow_check(&Ciphertext::new(private_key.p.clone()), &bound),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_decrypt_with_receipt() {
+    let private_key = common::shared_private_key();
+    let message = BigUint::from(1337u64);
+    let c = OkamotoUchiyama::encrypt(&message, &private_key.public_key);
+
+    let (result, receipt) = private_key.decrypt_with_receipt(&c);
+    assert_eq!(result.unwrap(), message);
+    assert_eq!(receipt.ciphertext_fingerprint, c.fingerprint());
+    assert!(receipt.exponentiation_commitment.is_some());
+
+    // Receipts of equal plaintexts, even zero, cannot be linked through their commitments
+    for m in [1337u64, 0] {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(m), &private_key.public_key);
+        let (_, first) = private_key.decrypt_with_receipt(&c);
+        let (_, second) = private_key.decrypt_with_receipt(&c);
+        assert_ne!(first.nonce, second.nonce);
+        assert_ne!(
+            first.exponentiation_commitment,
+            second.exponentiation_commitment
+        );
+    }
+
+    // Rejected ciphertexts still get a receipt
+    let zero = Ciphertext::new(BigUint::from(0u64));
+    let (result, receipt) = private_key.decrypt_with_receipt(&zero);
+    assert!(matches!(
+        result,
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+    assert_eq!(receipt.ciphertext_fingerprint, zero.fingerprint());
+    assert_ne!(receipt.ciphertext_fingerprint, c.fingerprint());
+    assert!(receipt.exponentiation_commitment.is_none());
+}
+
+#[test]
+fn test_max_safe_additions() {
+    // p = 2003, floor(2002 / 100) = 20
+    let private_key = common::small_private_key();
+    let max_term = BigUint::from(100u64);
+    let count = private_key.max_safe_additions(&max_term);
+    assert_eq!(count, BigUint::from(20u64));
+
+    // Summing that many terms just below the bound decrypts correctly
+    let term = OkamotoUchiyama::encrypt(&BigUint::from(99u64), &private_key.public_key);
+    let terms = vec![&term; 20];
+    let sum = private_key
+        .public_key
+        .homomorphic_encrypt_multiple(terms)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, &private_key),
+        BigUint::from(1980u64)
+    );
+
+    assert_eq!(
+        private_key.max_safe_additions(&BigUint::from(0u64)),
+        BigUint::from(0u64)
+    );
+}
+
+#[test]
+fn test_matches_public_key() {
+    let private_key = common::small_private_key();
+
+    // The public key loaded from its own PEM matches
+    let mut public_key = PublicKey::from_pem(&common::small_public_key().to_pem()).unwrap();
+    assert!(private_key.matches_public_key(&public_key));
+
+    // Precomputed tables do not matter
+    public_key.precompute_encryption_tables(4);
+    assert!(private_key.matches_public_key(&public_key));
+
+    // Another key, or the same modulus with another g, does not
+    assert!(!private_key.matches_public_key(common::shared_public_key()));
+    let other_g = PublicKey::new(
+        &public_key.n,
+        &(&public_key.g + 1u32),
+        &(&public_key.g + 1u32).modpow(&public_key.n, &public_key.n),
+    );
+    assert!(!private_key.matches_public_key(&other_g));
+}
+
+#[test]
+fn test_homomorphic_divide_constant() {
+    let private_key = common::shared_private_key();
+    let c = OkamotoUchiyama::encrypt(&BigUint::from(20u64), &private_key.public_key);
+
+    let quotient = private_key
+        .homomorphic_divide_constant(&c, &BigUint::from(4u64))
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&quotient, private_key),
+        BigUint::from(5u64)
+    );
+
+    // Multiples of p have no inverse
+    for k in [BigUint::from(0u64), private_key.p.clone()] {
+        assert!(matches!(
+            private_key.homomorphic_divide_constant(&c, &k),
+            Err(OkamotoUchiyamaError::NotInvertible)
+        ));
+    }
+}
+
+#[test]
+fn test_encrypted_bloom_filter() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let num_hashes = 3;
+
+    let mut filter: Vec<Ciphertext> = (0..64)
+        .map(|_| OkamotoUchiyama::encrypt(&BigUint::from(0u32), public_key))
+        .collect();
+
+    for (item, count) in [(&b"apple"[..], 3), (b"banana", 1), (b"cherry", 5)] {
+        for _ in 0..count {
+            public_key
+                .encrypted_bloom_increment(&mut filter, item, num_hashes)
+                .unwrap();
+        }
+    }
+
+    // Collisions can only raise an estimate
+    for (item, count) in [(&b"apple"[..], 3u32), (b"banana", 1), (b"cherry", 5)] {
+        let estimate = private_key
+            .bloom_estimate(&filter, item, num_hashes)
+            .unwrap();
+        assert!(estimate >= BigUint::from(count));
+        assert!(estimate <= BigUint::from(9u32));
+    }
+    assert_eq!(
+        private_key
+            .bloom_estimate(&filter, b"apple", num_hashes)
+            .unwrap(),
+        BigUint::from(3u32)
+    );
+
+    assert!(matches!(
+        public_key.encrypted_bloom_increment(&mut [], b"apple", num_hashes),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+    assert!(matches!(
+        private_key.bloom_estimate(&filter, b"apple", 0),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+}
+
+#[test]
+fn test_encrypted_hamming_weight() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let pattern = [1u32, 0, 1, 1, 0, 0, 1, 0, 1, 1, 1, 0];
+    let popcount = pattern.iter().sum::<u32>();
+    let bits: Vec<Ciphertext> = pattern
+        .iter()
+        .map(|&bit| OkamotoUchiyama::encrypt(&BigUint::from(bit), public_key))
+        .collect();
+
+    let weight = public_key.encrypted_hamming_weight(&bits).unwrap();
+    assert_eq!(
+        private_key.decrypt_count(&weight, bits.len()).unwrap(),
+        BigUint::from(popcount)
+    );
+
+    // An empty vector has no set bit
+    let weight = public_key.encrypted_hamming_weight(&[]).unwrap();
+    assert_eq!(
+        private_key.decrypt_count(&weight, 0).unwrap(),
+        BigUint::from(0u32)
+    );
+
+    // A non-bit input can push the count past the number of bits
+    let not_bits: Vec<Ciphertext> = [5u32, 1]
+        .iter()
+        .map(|&m| OkamotoUchiyama::encrypt(&BigUint::from(m), public_key))
+        .collect();
+    let weight = public_key.encrypted_hamming_weight(&not_bits).unwrap();
+    assert!(matches!(
+        private_key.decrypt_count(&weight, not_bits.len()),
+        Err(OkamotoUchiyamaError::CountOutOfRange)
+    ));
+
+    // A multiple of p is rejected instead of panicking in the decryption
+    assert!(matches!(
+        private_key.decrypt_count(&Ciphertext::new(private_key.p.clone()), 1),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
+
+#[test]
+fn test_budget_report() {
+    let private_key = common::shared_private_key();
+    let p = &private_key.p;
+    let max_term = BigUint::from(1_000_000u32);
+
+    let report = private_key.budget_report(&max_term);
+    assert_eq!(report.plaintext_modulus_bits, p.bits() as u64);
+    assert_eq!(report.max_message, p - 1u32);
+    assert_eq!(report.max_term, max_term);
+    assert_eq!(report.max_additions, (p - 1u32) / &max_term);
+
+    // The largest message decrypts to itself, the next one wraps to zero
+    let public_key = &private_key.public_key;
+    let c = OkamotoUchiyama::encrypt(&report.max_message, public_key);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&c, private_key),
+        report.max_message
+    );
+    let c = OkamotoUchiyama::encrypt(p, public_key);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&c, private_key),
+        BigUint::from(0u32)
+    );
+
+    let report = private_key.budget_report(&BigUint::from(0u32));
+    assert_eq!(report.max_additions, BigUint::from(0u32));
+}
+
+#[test]
+fn test_encrypted_records() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // (age, salary, department) rows
+    let rows: Vec<Vec<BigUint>> = [[34u32, 52_000, 1], [41, 61_500, 2], [29, 48_250, 1]]
+        .iter()
+        .map(|row| row.iter().map(|&field| BigUint::from(field)).collect())
+        .collect();
+    let records: Vec<Vec<Ciphertext>> = rows
+        .iter()
+        .map(|row| public_key.encrypt_record(row))
+        .collect();
+
+    // Records decrypt field by field, in order
+    for (record, row) in records.iter().zip(&rows) {
+        assert_eq!(&private_key.decrypt_record(record).unwrap(), row);
+    }
+
+    // Sum the salary column across the records
+    let salaries: Vec<Ciphertext> = records.iter().map(|record| record[1].clone()).collect();
+    let sums = public_key.homomorphic_sum_columns(&[salaries]).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sums[0], private_key),
+        BigUint::from(52_000u32 + 61_500 + 48_250)
+    );
+
+    // A record holding an invalid ciphertext is rejected
+    let mut tampered = records[0].clone();
+    tampered[2] = Ciphertext::new(public_key.n.clone());
+    assert!(private_key.decrypt_record(&tampered).is_err());
+}
@@ -0,0 +1,297 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::pem::PemEncodable;
+use okamoto_uchiyama::{OkamotoUchiyama, PublicKey};
+
+#[test]
+fn test_accepts() {
+    let public_key = common::small_public_key();
+
+    // A proper encryption is accepted
+    let c = OkamotoUchiyama::encrypt(&BigUint::from(42u64), &public_key);
+    assert!(public_key.accepts(&c));
+
+    // Out of range values are rejected
+    assert!(!public_key.accepts(&Ciphertext::new(BigUint::from(0u64))));
+    assert!(!public_key.accepts(&Ciphertext::new(public_key.n.clone())));
+    assert!(!public_key.accepts(&Ciphertext::new(&public_key.n + 1u32)));
+
+    // Values sharing a factor with n are rejected
+    assert!(!public_key.accepts(&Ciphertext::new(BigUint::from(2003u64))));
+    assert!(!public_key.accepts(&Ciphertext::new(BigUint::from(2351u64))));
+
+    // Homomorphic operations use the same precondition
+    let bad = Ciphertext::new(BigUint::from(2003u64));
+    assert!(matches!(
+        public_key.homomorphic_encrypt_two(&c, &bad),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+    assert!(matches!(
+        public_key.homomorphic_encrypt_multiple(vec![&c, &Ciphertext::new(public_key.n.clone())]),
+        Err(OkamotoUchiyamaError::CipherTooLarge)
+    ));
+}
+
+#[test]
+fn test_normalized() {
+    let public_key = common::small_public_key();
+
+    // Un-reduced g and h become canonical
+    let unreduced = PublicKey::new(
+        &public_key.n,
+        &(&public_key.g + &public_key.n),
+        &(&public_key.h + &public_key.n * 2u32),
+    );
+    assert_eq!(unreduced.clone().normalized().unwrap(), public_key);
+
+    // Deserialization normalizes the key
+    let parsed = PublicKey::from_pem(&unreduced.to_pem()).unwrap();
+    assert_eq!(parsed, public_key);
+
+    // A zero modulus is rejected
+    let zero_n = PublicKey::new(&BigUint::from(0u64), &public_key.g, &public_key.h);
+    assert!(matches!(
+        zero_n.normalized(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+
+    // So is g reducing to 1
+    let trivial_g = PublicKey::new(&public_key.n, &(&public_key.n + 1u32), &public_key.h);
+    assert!(matches!(
+        PublicKey::from_pem(&trivial_g.to_pem()),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_ciphertext_bit_length() {
+    let public_key = common::shared_public_key();
+    let n_bits = public_key.n.bits() as u64;
+
+    // A proper encryption is about as large as n
+    for m in [0u64, 1, 1337] {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(m), public_key);
+        assert!(c.bit_length() <= n_bits);
+        assert!(c.bit_length() > n_bits - 64);
+        assert!(!c.looks_suspicious(public_key));
+    }
+
+    // A tiny hand-crafted value is flagged
+    let tiny = Ciphertext::new(BigUint::from(12345u64));
+    assert_eq!(tiny.bit_length(), 14);
+    assert!(tiny.looks_suspicious(public_key));
+
+    // Values not below n are suspicious
+    assert!(Ciphertext::new(public_key.n.clone()).looks_suspicious(public_key));
+}
+
+#[test]
+fn test_inv_g_mod_n() {
+    let public_key = common::shared_public_key();
+
+    let inverse = public_key.inv_g_mod_n().unwrap();
+    assert_eq!(
+        (&public_key.g * &inverse) % &public_key.n,
+        BigUint::from(1u64)
+    );
+
+    // The cached inverse is reused and does not affect equality
+    assert_eq!(public_key.inv_g_mod_n().unwrap(), inverse);
+    assert_eq!(
+        public_key,
+        &PublicKey::from_pem(&public_key.to_pem()).unwrap()
+    );
+
+    // The inverse follows a g modified after caching, here g^2 which stays invertible
+    let mut modified = public_key.clone();
+    modified.g = (&public_key.g * &public_key.g) % &public_key.n;
+    let modified_inverse = modified.inv_g_mod_n().unwrap();
+    assert_ne!(modified_inverse, inverse);
+    assert_eq!(
+        (&modified.g * &modified_inverse) % &modified.n,
+        BigUint::from(1u64)
+    );
+
+    // A g sharing a factor with n has no inverse
+    let public_key = PublicKey::new(
+        &BigUint::from(12u64),
+        &BigUint::from(3u64),
+        &BigUint::from(3u64),
+    );
+    assert!(matches!(
+        public_key.inv_g_mod_n(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+    assert!(matches!(
+        PublicKey::default().inv_g_mod_n(),
+        Err(OkamotoUchiyamaError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_diff() {
+    let public_key = common::small_public_key();
+    assert!(public_key.diff(&public_key.clone()).is_empty());
+
+    // Keys differing only in h
+    let other = PublicKey::new(&public_key.n, &public_key.g, &(&public_key.h + 1u32));
+    assert_eq!(public_key.diff(&other), vec!["h"]);
+
+    // Private keys report the public key fields first
+    let private_key = common::small_private_key();
+    let mut other_private_key = private_key.clone();
+    other_private_key.public_key = other;
+    other_private_key.q = &private_key.q + 2u32;
+    assert_eq!(private_key.diff(&other_private_key), vec!["h", "q"]);
+    assert!(private_key.diff(&private_key.clone()).is_empty());
+}
+
+#[test]
+fn test_validate_ciphertexts() {
+    let public_key = common::small_public_key();
+    let mut ciphertexts: Vec<Ciphertext> = (0..5u64)
+        .map(|m| OkamotoUchiyama::encrypt(&BigUint::from(m), &public_key))
+        .collect();
+    assert!(public_key.validate_ciphertexts(&ciphertexts).is_ok());
+    assert!(public_key.validate_ciphertexts(&[]).is_ok());
+
+    // A multiple of p is not coprime with n
+    ciphertexts[3] = Ciphertext::new(BigUint::from(2003u64 * 7));
+    assert!(matches!(
+        public_key.validate_ciphertexts(&ciphertexts),
+        Err(OkamotoUchiyamaError::InvalidCiphertextAt(3))
+    ));
+
+    // The first invalid ciphertext is reported
+    ciphertexts[1] = Ciphertext::new(public_key.n.clone());
+    assert!(matches!(
+        public_key.validate_ciphertexts(&ciphertexts),
+        Err(OkamotoUchiyamaError::InvalidCiphertextAt(1))
+    ));
+}
+
+#[test]
+fn test_le_bytes_reconstruct_values() {
+    let public_key = common::shared_public_key();
+
+    assert_eq!(
+        BigUint::from_bytes_le(&public_key.n_le_bytes()),
+        public_key.n
+    );
+    assert_eq!(
+        BigUint::from_bytes_le(&public_key.g_le_bytes()),
+        public_key.g
+    );
+    assert_eq!(
+        BigUint::from_bytes_le(&public_key.h_le_bytes()),
+        public_key.h
+    );
+    assert_eq!(
+        public_key.n_le_bytes().len(),
+        public_key.ciphertext_byte_len()
+    );
+    assert_ne!(public_key.n_le_bytes().last(), Some(&0));
+
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(42u32), public_key);
+    let bytes = ciphertext.value_le_bytes();
+    assert_eq!(&BigUint::from_bytes_le(&bytes), ciphertext.value());
+    let mut be = ciphertext.value().to_bytes_be();
+    be.reverse();
+    assert_eq!(bytes, be);
+
+    assert_eq!(
+        Ciphertext::new(BigUint::from(0u32)).value_le_bytes(),
+        vec![0]
+    );
+}
+
+#[test]
+fn test_directory_entry() {
+    let public_key = common::shared_public_key();
+
+    let entry = public_key.to_directory_entry();
+    assert!(entry.starts_with("{\"spki\":\""));
+    assert!(entry.contains(&format!("\"modulus_bits\":{}", public_key.n.bits())));
+    assert!(entry.contains(&format!(
+        "\"security_bits\":{}",
+        public_key.estimated_security_bits()
+    )));
+    assert_eq!(
+        &PublicKey::from_directory_entry(&entry).unwrap(),
+        public_key
+    );
+
+    // Members may come in any order, with whitespace between tokens
+    let members: Vec<&str> = entry[1..entry.len() - 1].split(',').collect();
+    let reordered = format!("{{ {} ,\n {} }}", members[1..].join(" , "), members[0]);
+    assert_eq!(
+        &PublicKey::from_directory_entry(&reordered).unwrap(),
+        public_key
+    );
+
+    // The fingerprint of another key is detected
+    let other = common::small_public_key();
+    let other_entry = other.to_directory_entry();
+    let fingerprint_of = |entry: &str| {
+        let start = entry.find("\"fingerprint\":\"").unwrap() + 15;
+        entry[start..start + 64].to_string()
+    };
+    let forged = entry.replace(&fingerprint_of(&entry), &fingerprint_of(&other_entry));
+    assert!(matches!(
+        PublicKey::from_directory_entry(&forged),
+        Err(OkamotoUchiyamaError::FingerprintMismatch)
+    ));
+
+    let truncated = entry.replace("\"modulus_bits\"", "\"bits\"");
+    assert!(matches!(
+        PublicKey::from_directory_entry(&truncated),
+        Err(OkamotoUchiyamaError::DecodingError)
+    ));
+    assert!(PublicKey::from_directory_entry("not json").is_err());
+}
+
+#[test]
+fn test_estimated_security_bits() {
+    let public_key = |bits: usize| {
+        PublicKey::new(
+            &(BigUint::from(1u32) << (bits - 1)),
+            &BigUint::from(2u32),
+            &BigUint::from(2u32),
+        )
+    };
+    assert_eq!(public_key(1024).estimated_security_bits(), 86);
+    assert_eq!(public_key(2048).estimated_security_bits(), 116);
+    assert!(
+        public_key(4096).estimated_security_bits() > public_key(2048).estimated_security_bits()
+    );
+}
+
+#[test]
+fn test_homomorphic_encrypt_multiple_limited() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let ciphertexts: Vec<Ciphertext> = (1..=4u64)
+        .map(|m| OkamotoUchiyama::encrypt(&BigUint::from(m), public_key))
+        .collect();
+
+    // A batch at the limit is processed
+    let sum = public_key
+        .homomorphic_encrypt_multiple_limited(ciphertexts.iter().collect(), 4)
+        .unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&sum, private_key),
+        BigUint::from(10u64)
+    );
+
+    // A batch over the limit is rejected before any validation
+    let mut over_limit: Vec<&Ciphertext> = ciphertexts.iter().collect();
+    let invalid = Ciphertext::new(BigUint::from(0u32));
+    over_limit.push(&invalid);
+    assert!(matches!(
+        public_key.homomorphic_encrypt_multiple_limited(over_limit, 4),
+        Err(OkamotoUchiyamaError::BatchTooLarge)
+    ));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::sealed_ciphertext::SealedCiphertext;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_seal_and_open() {
+    let private_key = common::shared_private_key();
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(42u32), &private_key.public_key);
+
+    let sealed = ciphertext.seal(b"storage key");
+    let opened = sealed.open(b"storage key").unwrap();
+    assert_eq!(opened, ciphertext);
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&opened, private_key),
+        BigUint::from(42u32)
+    );
+
+    // The tag is deterministic and survives storage as separate parts
+    let stored = SealedCiphertext::from_parts(ciphertext.clone(), *sealed.tag());
+    assert_eq!(stored, ciphertext.seal(b"storage key"));
+    assert!(stored.open(b"storage key").is_ok());
+}
+
+#[test]
+fn test_open_rejects_tampering() {
+    let public_key = common::shared_public_key();
+    let ciphertext = OkamotoUchiyama::encrypt(&BigUint::from(42u32), public_key);
+    let sealed = ciphertext.seal(b"storage key");
+
+    // A homomorphic operation on the sealed value breaks the tag
+    let doubled = public_key
+        .homomorphic_encrypt_two(sealed.ciphertext_unverified(), &ciphertext)
+        .unwrap();
+    let tampered = SealedCiphertext::from_parts(doubled, *sealed.tag());
+    assert!(matches!(
+        tampered.open(b"storage key"),
+        Err(OkamotoUchiyamaError::InvalidTag)
+    ));
+
+    let mut tag = *sealed.tag();
+    tag[0] ^= 1;
+    let tampered = SealedCiphertext::from_parts(ciphertext.clone(), tag);
+    assert!(matches!(
+        tampered.open(b"storage key"),
+        Err(OkamotoUchiyamaError::InvalidTag)
+    ));
+
+    assert!(matches!(
+        sealed.open(b"another key"),
+        Err(OkamotoUchiyamaError::InvalidTag)
+    ));
+}
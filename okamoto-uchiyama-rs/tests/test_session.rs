@@ -0,0 +1,47 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::session::HomomorphicSession;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_homomorphic_session() {
+    let private_key = common::shared_private_key();
+    let session = HomomorphicSession::new(&private_key.public_key);
+
+    // (3 * (10 + 20 + 30) + 7) * 2 = 374
+    let ciphertexts: Vec<_> = [10u64, 20, 30]
+        .iter()
+        .map(|&m| session.encrypt(&BigUint::from(m)))
+        .collect();
+    let sum = session.add_multiple(&ciphertexts).unwrap();
+    let tripled = session
+        .multiply_constant(&sum, &BigUint::from(3u64))
+        .unwrap();
+    let shifted = session
+        .add_constant(&tripled, &BigUint::from(7u64))
+        .unwrap();
+    let doubled = session.add(&shifted, &shifted).unwrap();
+    let result = session
+        .scale_secret(&doubled, &BigUint::from(1u64))
+        .unwrap();
+
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&result, private_key),
+        BigUint::from(374u64)
+    );
+
+    // Ciphertexts from a session under another key are rejected
+    let other_key = common::small_public_key();
+    let other_session = HomomorphicSession::new(&other_key);
+    let foreign = other_session.encrypt(&BigUint::from(1u64));
+    assert!(matches!(
+        session.add(&result, &foreign),
+        Err(OkamotoUchiyamaError::KeyMismatch)
+    ));
+    assert!(matches!(
+        session.multiply_constant(&foreign, &BigUint::from(2u64)),
+        Err(OkamotoUchiyamaError::KeyMismatch)
+    ));
+}
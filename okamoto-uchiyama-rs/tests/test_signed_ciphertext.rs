@@ -0,0 +1,134 @@
+mod common;
+
+use num_bigint_dig::{BigInt, BigUint};
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::signed_ciphertext::SignedCiphertext;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_signed_add() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let a = SignedCiphertext::encrypt(&BigInt::from(-1500), public_key).unwrap();
+    let b = SignedCiphertext::encrypt(&BigInt::from(200), public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&a).unwrap(),
+        BigInt::from(-1500)
+    );
+
+    let sum = a.homomorphic_add(&b, public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&sum).unwrap(),
+        BigInt::from(-1300)
+    );
+}
+
+#[test]
+fn test_signed_subtract_crossing_zero() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let a = SignedCiphertext::encrypt(&BigInt::from(10), public_key).unwrap();
+    let b = SignedCiphertext::encrypt(&BigInt::from(25), public_key).unwrap();
+
+    let difference = a.homomorphic_subtract(&b, public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&difference).unwrap(),
+        BigInt::from(-15)
+    );
+
+    let difference = b.homomorphic_subtract(&a, public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&difference).unwrap(),
+        BigInt::from(15)
+    );
+
+    let zero = a.homomorphic_subtract(&a, public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&zero).unwrap(),
+        BigInt::from(0)
+    );
+}
+
+#[test]
+fn test_signed_multiply_by_negative_constant() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    let a = SignedCiphertext::encrypt(&BigInt::from(7), public_key).unwrap();
+    let product = a.multiply_constant(&BigInt::from(-6), public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&product).unwrap(),
+        BigInt::from(-42)
+    );
+
+    let b = SignedCiphertext::encrypt(&BigInt::from(-7), public_key).unwrap();
+    let product = b.multiply_constant(&BigInt::from(-6), public_key).unwrap();
+    assert_eq!(
+        private_key.decrypt_signed_ct(&product).unwrap(),
+        BigInt::from(42)
+    );
+}
+
+#[test]
+fn test_subtraction_context() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Subtract the same mean from every value
+    let mean = OkamotoUchiyama::encrypt(&BigUint::from(50u64), public_key);
+    let context = public_key.subtraction_context(&mean).unwrap();
+
+    for value in [50u64, 75, 20] {
+        let c = OkamotoUchiyama::encrypt(&BigUint::from(value), public_key);
+
+        // The fast path matches the slow path
+        let fast = context.homomorphic_subtract_fast(&c).unwrap();
+        let slow = SignedCiphertext::from(c)
+            .homomorphic_subtract(&SignedCiphertext::from(mean.clone()), public_key)
+            .unwrap();
+        assert_eq!(&fast, slow.ciphertext());
+
+        assert_eq!(
+            private_key
+                .decrypt_signed_ct(&SignedCiphertext::from(fast))
+                .unwrap(),
+            BigInt::from(value as i64 - 50)
+        );
+    }
+}
+
+#[test]
+fn test_signed_keeps_tag_and_rejects_invalid() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+
+    // Operations on tagged ciphertexts stay tagged
+    let tagged = SignedCiphertext::from(public_key.encrypt_tagged(&BigUint::from(5u64)));
+    let negated = tagged
+        .multiply_constant(&BigInt::from(-1), public_key)
+        .unwrap();
+    assert_eq!(
+        negated.ciphertext().key_id(),
+        Some(&public_key.fingerprint())
+    );
+    let b = SignedCiphertext::encrypt(&BigInt::from(3), public_key).unwrap();
+    let difference = b.homomorphic_subtract(&tagged, public_key).unwrap();
+    assert_eq!(
+        difference.ciphertext().key_id(),
+        Some(&public_key.fingerprint())
+    );
+    assert_eq!(
+        private_key.decrypt_signed_ct(&difference).unwrap(),
+        BigInt::from(-2)
+    );
+
+    // A multiple of p is rejected instead of panicking in the decryption
+    let invalid = SignedCiphertext::from(Ciphertext::new(private_key.p.clone()));
+    assert!(matches!(
+        private_key.decrypt_signed_ct(&invalid),
+        Err(OkamotoUchiyamaError::InvalidCiphertext)
+    ));
+}
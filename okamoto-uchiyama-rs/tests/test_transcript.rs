@@ -0,0 +1,85 @@
+mod common;
+
+use num_bigint_dig::BigUint;
+use okamoto_uchiyama::crypto::ciphertext::Ciphertext;
+use okamoto_uchiyama::crypto::transcript::HomomorphicOp;
+use okamoto_uchiyama::error::OkamotoUchiyamaError;
+use okamoto_uchiyama::OkamotoUchiyama;
+
+#[test]
+fn test_verify_sum_transcript() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let inputs: Vec<Ciphertext> = [5u32, 10, 20]
+        .iter()
+        .map(|&m| OkamotoUchiyama::encrypt(&BigUint::from(m), public_key))
+        .collect();
+
+    let result = public_key.homomorphic_sum_of(&inputs).unwrap();
+    assert!(private_key
+        .verify_transcript(&inputs, &result, HomomorphicOp::Sum)
+        .unwrap());
+
+    // A result that leaves an input out, or adds one, is not derived from the inputs
+    let partial = public_key.homomorphic_sum_of(&inputs[..2]).unwrap();
+    assert!(!private_key
+        .verify_transcript(&inputs, &partial, HomomorphicOp::Sum)
+        .unwrap());
+    let extra = OkamotoUchiyama::encrypt(&BigUint::from(1000u32), public_key);
+    let inflated = public_key.homomorphic_encrypt_two(&result, &extra).unwrap();
+    assert!(!private_key
+        .verify_transcript(&inputs, &inflated, HomomorphicOp::Sum)
+        .unwrap());
+}
+
+#[test]
+fn test_verify_weighted_sum_transcript() {
+    let private_key = common::shared_private_key();
+    let public_key = &private_key.public_key;
+    let inputs: Vec<Ciphertext> = [5u32, 10]
+        .iter()
+        .map(|&m| OkamotoUchiyama::encrypt(&BigUint::from(m), public_key))
+        .collect();
+    let weights = vec![BigUint::from(3u32), BigUint::from(2u32)];
+
+    let scaled: Vec<Ciphertext> = inputs
+        .iter()
+        .zip(&weights)
+        .map(|(c, w)| c.with_key(public_key).mul_const(w).unwrap().finish())
+        .collect();
+    let result = public_key.homomorphic_sum_of(&scaled).unwrap();
+    assert_eq!(
+        OkamotoUchiyama::decrypt(&result, private_key),
+        BigUint::from(35u32)
+    );
+    assert!(private_key
+        .verify_transcript(
+            &inputs,
+            &result,
+            HomomorphicOp::WeightedSum {
+                weights: weights.clone()
+            }
+        )
+        .unwrap());
+
+    // Swapped weights describe another computation
+    let swapped = vec![weights[1].clone(), weights[0].clone()];
+    assert!(!private_key
+        .verify_transcript(
+            &inputs,
+            &result,
+            HomomorphicOp::WeightedSum { weights: swapped }
+        )
+        .unwrap());
+
+    assert!(matches!(
+        private_key.verify_transcript(
+            &inputs,
+            &result,
+            HomomorphicOp::WeightedSum {
+                weights: weights[..1].to_vec()
+            }
+        ),
+        Err(OkamotoUchiyamaError::LengthMismatch)
+    ));
+}